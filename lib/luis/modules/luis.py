@@ -1,4 +1,14 @@
-import requests,json
+import requests,json,re
+
+## intents the interactive paper understands as they are named in the LUIS app, see the LU response section of the README
+contract_intents=["mark_for_review","skip","clear_marks","count_check","answer_start","dictation",
+    "answer_replace","answer_clear","answer_stop","answer_insert","answer_new_paragraph","answer_delete",
+    "answer_replace_phrase","answer_move","answer_undo","answer_read","answer_spell","answer_choose",
+    "answer_change_choice","answer_deselect","choice_check","answer_math","answer_confirm","take_note",
+    "read_notes","list_notes","edit_note","delete_note","unmark","unskip","undo","redo","go_back",
+    "go_forward","where_before"]
+## entities that carry what was said, the paper reads it from their child
+text_entities=["answer_text","text_unit","text_position","option_label","note_text"]
 
 def endpoint(text):	
     
@@ -68,12 +78,21 @@ def endpoint(text):
             tex=u['prediction']['entities']["$instance"][entity][i]['text']
             if "list" in ent or "datetime" in ent:continue ## not printing list entities (exact matches),prebuilt date entities 
 ## checking for insertion and deletion and changing the intent name from Navigation.
-            if "delete" in ent or "write" in ent or "insert" in ent :
+            if ("delete" in ent or "write" in ent or "insert" in ent) and top_intent not in contract_intents:
                 resp['top_intent']="edit"
                 if "description" in ent and tex.strip() in ["that","this","the","this specific","that specific","this entire","that entire"]:
                    tex="typeofnav"
-            if "copy" in ent :
+            if "copy" in ent and top_intent not in contract_intents:
                 resp['top_intent']="copy"
+## dictation, notes and option labels keep what was said as children, "B and D" gives a child for each option
+            if ent in text_entities:
+                values=[tex]
+                if ent=="option_label":
+                    values=[value for value in re.split(r",| and | or ",tex) if value.strip()]
+                main_entity['entity']=ent
+                main_entity['CHILD']=[{'entity':ent,'value':value.strip()} for value in values]
+                entities.append(main_entity)
+                continue
 ## will be added later if section is not present
             if "total" in ent:
                 status_total['entity']=ent
@@ -1,4 +1,5 @@
 use serde::Serialize;
+use crate::question_paper::BuildErrors;
 
 /// Intent parsing errors
 #[derive(Debug, Serialize)]
@@ -6,6 +7,15 @@ pub enum Errors {
     NetworkError(String),
     ParsingError,
     InvalidInput(String),
-    InternalError(String)   
+    InternalError(String),
+    /// The uploaded paper could not be built
+    BuildError(BuildErrors)
+}
+
+impl Errors {
+    /// Errors caused by the request rather than by the server
+    pub fn is_client_error(&self) -> bool {
+        matches!(self, Errors::InvalidInput(_) | Errors::BuildError(_))
+    }
 }
 
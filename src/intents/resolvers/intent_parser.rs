@@ -1,5 +1,6 @@
-use super::{LuResponse, Entity, EntityType};
-use crate::question_paper::{Intent, Reference, Read, Write, MetaIntent};
+use super::{LuResponse, Entity, EntityChild, EntityType, TopIntents};
+use crate::question_paper::{Intent, Reference, Read, Write, BulkAction, AnswerEdit, ChoiceEdit, CountTarget, Filter, MetaIntent};
+use crate::question_paper::{TextUnit, TextPosition, AnswerRead, NoteRead, NoteRef, Visit};
 
 
 use serde::Deserialize;
@@ -9,28 +10,42 @@ use serde::Deserialize;
 /// Response parser modes
 #[derive(Debug)]
 enum Modes {
-    Read, Write, Meta
+    Read, Write, Meta, AnswerRead, Notes, Visit
 }
 
 /// Parses an intent response from LU into an intent that can be understood by the question paper
 #[derive(Debug)]
 pub struct IntentParser {
-    mode: Option<Modes>
+    mode: Option<Modes>,
+    top_intent: Option<TopIntents>,
+    // the input as spoken
+    query: String
 }
 
 
 impl IntentParser {
     pub fn new() -> Self {
         IntentParser{
-            mode: None
+            mode: None,
+            top_intent: None,
+            query: String::new()
         }
     }
 
     pub fn parse(&mut self, intent: LuResponse) -> Vec<Intent> {
+        self.top_intent = Some(intent.top_intent());
+        self.query = intent.query.clone();
+
         if intent.is_read(){
             self.mode = Some(Modes::Read);
         }else if intent.is_meta(){
             self.mode = Some(Modes::Meta);
+        }else if intent.is_answer_read(){
+            self.mode = Some(Modes::AnswerRead);
+        }else if intent.is_note_read(){
+            self.mode = Some(Modes::Notes);
+        }else if intent.is_visit(){
+            self.mode = Some(Modes::Visit);
         }else{
             self.mode = Some(Modes::Write);
         }
@@ -43,6 +58,9 @@ impl IntentParser {
         match self.mode {
             Some(Modes::Write) => self.create_write_intent(entities),
             Some(Modes::Meta) =>self.create_meta_intent(entities),
+            Some(Modes::AnswerRead) => self.create_answer_read_intent(entities),
+            Some(Modes::Notes) => self.create_note_read_intent(entities),
+            Some(Modes::Visit) => self.create_visit_intent(),
             _ => self.create_read_intent(entities)
         }
         
@@ -51,47 +69,149 @@ impl IntentParser {
     fn create_meta_intent(&mut self, mut entities: Vec<Entity>) -> Vec<Intent> {
         let mut intents = Vec::new();
 
+        // time and marks checks are resolved by LU without a locator entity
+        match self.top_intent {
+            Some(TopIntents::TimeCheck) => return vec![Intent::Meta(MetaIntent::Duration)],
+            Some(TopIntents::MarksCheck) => return vec![Intent::Meta(MetaIntent::TotalMarks)],
+            Some(TopIntents::Count) => return self.create_count_intent(&entities).into_iter().collect(),
+            _ => ()
+        }
+
+        if entities.is_empty(){
+            return intents;
+        }
+
         let top = entities.remove(0);
 
         match top.entity_type() {
             EntityType::Marked => intents.push(Intent::Meta(MetaIntent::Marked)),
             EntityType::Skipped => intents.push(Intent::Meta(MetaIntent::Skipped)),
+            EntityType::Paper => intents.push(Intent::Meta(MetaIntent::Paper)),
+            EntityType::Progress => intents.push(Intent::Meta(MetaIntent::Progress)),
+            EntityType::Time => intents.push(Intent::Meta(MetaIntent::Duration)),
+            EntityType::Marks => intents.push(Intent::Meta(MetaIntent::TotalMarks)),
             _ => ()
         }
 
         intents
     }
 
-    fn create_read_intent(&mut self, entities: Vec<Entity>) -> Vec<Intent> {
-        // create an array of intents
-        let mut intents = Vec::new();
+    // "how many questions are left in section B", the counted kind is named without a child
+    fn create_count_intent(&mut self, entities: &[Entity]) -> Option<Intent> {
+        let mut target = None;
+        let mut scope = Vec::new();
+        let mut filters = Vec::new();
+
+        for entity in entities {
+            if target.is_none() && !entity.has_children() {
+                let counted = match entity.entity_type() {
+                    EntityType::Question => Some(CountTarget::Questions),
+                    EntityType::Section => Some(CountTarget::Sections),
+                    EntityType::Page => Some(CountTarget::Pages),
+                    EntityType::Passage => Some(CountTarget::Passages),
+                    EntityType::Figure => Some(CountTarget::Figures),
+                    EntityType::Table => Some(CountTarget::Tables),
+                    _ => None
+                };
+
+                if counted.is_some() {
+                    target = counted;
+                    continue;
+                }
+            }
+
+            if let Some(filter) = self.parse_filter(entity) {
+                filters.push(filter);
+                continue;
+            }
 
-        let mut prev = 0;
-        for entity in &entities {
-            let reference = self.get_reference(entity, prev).expect("could not compute the reference");
+            let reference = self.get_reference(entity, 0).ok()?;
 
-            intents.push(self.parse_read_intent(entity, reference));
-           prev += 1;
+            scope.push(self.parse_read(entity, reference));
         }
 
-        intents
+        scope.sort_by_key(Self::scope_rank);
+
+        Some(Intent::Meta(MetaIntent::Count(target?, scope, filters)))
+    }
+
+    // "go back", "go forward", "where was I before"
+    fn create_visit_intent(&mut self) -> Vec<Intent> {
+        let visit = match self.top_intent {
+            Some(TopIntents::GoBack) => Visit::Back,
+            Some(TopIntents::GoForward) => Visit::Forward,
+            Some(TopIntents::WhereBefore) => Visit::Before,
+            _ => return vec![]
+        };
+
+        vec![Intent::VisitIntent(visit)]
+    }
+
+    fn create_read_intent(&mut self, entities: Vec<Entity>) -> Vec<Intent> {
+        let mut reads = self.parse_reads(&entities);
+
+        // a chain such as "question 3 of section 2" is resolved as one scoped read
+        let read = match reads.len() {
+            0 => return vec![],
+            1 => reads.remove(0),
+            _ => Read::Scoped(reads)
+        };
+
+        vec![Intent::ReadIntent(read)]
     }
 
     fn create_write_intent(&mut self, mut entities: Vec<Entity>) -> Vec<Intent> {
+        match self.top_intent {
+            // undo covers answer edits as well as every other write
+            Some(TopIntents::Undo) | Some(TopIntents::AnswerUndo) => return vec![Intent::WriteIntent(Write::Undo)],
+            Some(TopIntents::Redo) => return vec![Intent::WriteIntent(Write::Redo)],
+            Some(TopIntents::Unmark) | Some(TopIntents::Unskip) => return vec![self.create_unmark_intent(&entities)],
+            _ => ()
+        }
+
+        if let Some(intent) = self.create_answer_intent(&entities) {
+            return vec![intent];
+        }
+
+        if let Some(intent) = self.create_choice_intent(&entities) {
+            return vec![intent];
+        }
+
+        if let Some(intent) = self.create_note_intent(&entities) {
+            return vec![intent];
+        }
+
+        // "clear all marks" has no action entity of its own
+        if let Some(TopIntents::ClearMarks) = self.top_intent {
+            return vec![self.create_bulk_write(BulkAction::ClearMark, &entities, vec![Filter::Marked])];
+        }
+
+        if entities.is_empty(){
+            return vec![];
+        }
+
         // create a write intent array
         let top = entities.remove(0);
 
         let mut ret = Vec::new();
 
-        let mut reads = Vec::new();
-        let mut prev = 0;
+        // "all" and "the rest" apply the write to every matching question
+        let is_bulk = entities.iter().any(|entity| matches!(entity.entity_type(), EntityType::All | EntityType::Rest));
 
-        for entity in entities {
-            let reference = self.get_reference(&entity, prev).expect("could not get the reference");
+        if is_bulk {
+            let action = match top.entity_type() {
+                EntityType::Marked => BulkAction::Mark,
+                EntityType::Skipped => BulkAction::Skip,
+                _ => return ret
+            };
+
+            ret.push(self.create_bulk_write(action, &entities, vec![]));
 
-            reads.push(self.parse_read(&entity, reference));
+            return ret;
         }
 
+        let reads = self.parse_reads(&entities);
+
         match top.entity_type() {
             EntityType::Marked => {
                 // create a locator marked from the remaining items
@@ -109,12 +229,299 @@ impl IntentParser {
 
         ret
     }
-    // create a read intent
-    fn parse_read_intent(&self, entity: &Entity, reference: Reference) -> Intent {
 
-        let read_intent = self.parse_read(entity, reference);
+    // answering intents, the question entities name the question answered
+    fn create_answer_intent(&mut self, entities: &[Entity]) -> Option<Intent> {
+        // dictated text comes as an entity when LU separates it from the command, otherwise it is the whole input
+        let texts: Vec<String> = entities.iter()
+            .filter(|entity| entity.entity_type() == EntityType::Text)
+            .filter_map(|entity| entity.child().ok())
+            .map(|child| child.get_text())
+            .collect();
+        let text = texts.first().cloned().unwrap_or_else(|| self.query.clone());
+
+        let unit = Self::text_unit(entities);
+        let position = Self::text_position(entities);
+
+        let edit = match self.top_intent {
+            Some(TopIntents::AnswerStart) => AnswerEdit::Start,
+            Some(TopIntents::Dictation) => AnswerEdit::Append(text),
+            Some(TopIntents::AnswerReplace) => AnswerEdit::Replace(text),
+            Some(TopIntents::AnswerClear) => AnswerEdit::Clear,
+            Some(TopIntents::AnswerStop) => AnswerEdit::Stop,
+            Some(TopIntents::AnswerInsert) => AnswerEdit::Insert(text),
+            Some(TopIntents::AnswerNewParagraph) => AnswerEdit::InsertParagraph,
+            Some(TopIntents::AnswerDelete) => AnswerEdit::Delete(unit, position),
+            // "replace 'there' with 'their'" carries both phrases
+            Some(TopIntents::AnswerReplacePhrase) => match texts.as_slice() {
+                [from, to] => AnswerEdit::ReplacePhrase(from.clone(), to.clone()),
+                _ => return None
+            },
+            Some(TopIntents::AnswerMove) => AnswerEdit::MoveCursor(unit, position),
+            Some(TopIntents::AnswerMath) => AnswerEdit::Math(text),
+            // "the second one"
+            Some(TopIntents::AnswerConfirm) => AnswerEdit::ConfirmMath(Self::choice_number(&text)),
+            _ => return None
+        };
+
+        let reads = self.parse_answer_reads(entities);
 
-        Intent::ReadIntent(read_intent)
+        // "start rough work", "read back my rough work"
+        if Self::has_entity(entities, EntityType::Rough) {
+            return Some(Intent::WriteIntent(Write::RoughWork(reads, edit)));
+        }
+
+        Some(Intent::WriteIntent(Write::Answer(reads, edit)))
+    }
+
+    // "unmark question 5", "I'm not skipping this after all", "unskip all questions in section B"
+    fn create_unmark_intent(&mut self, entities: &[Entity]) -> Intent {
+        let unmark = matches!(self.top_intent, Some(TopIntents::Unmark));
+
+        // the status named in the request is what is taken away, not a locator
+        let entities: Vec<Entity> = entities.iter()
+            .filter(|entity| !matches!(entity.entity_type(), EntityType::Marked | EntityType::Skipped))
+            .cloned()
+            .collect();
+
+        let is_bulk = entities.iter().any(|entity| matches!(entity.entity_type(), EntityType::All | EntityType::Rest));
+
+        match (is_bulk, unmark) {
+            (true, true) => self.create_bulk_write(BulkAction::ClearMark, &entities, vec![Filter::Marked]),
+            (true, false) => self.create_bulk_write(BulkAction::ClearSkip, &entities, vec![Filter::Skipped]),
+            (false, true) => Intent::WriteIntent(Write::Unmark(self.parse_reads(&entities))),
+            (false, false) => Intent::WriteIntent(Write::Unskip(self.parse_reads(&entities)))
+        }
+    }
+
+    // "note that the units are in metres", "change that note to ...", "delete my note on question 4"
+    fn create_note_intent(&mut self, entities: &[Entity]) -> Option<Intent> {
+        // the note as said when LU separates it from the command, otherwise the whole input
+        let text = entities.iter()
+            .filter(|entity| entity.entity_type() == EntityType::NoteText)
+            .filter_map(|entity| entity.child().ok())
+            .map(|child| child.get_text())
+            .next()
+            .unwrap_or_else(|| self.query.clone());
+
+        let reads = self.parse_answer_reads(entities);
+
+        // a note without a locator is the one last taken or read
+        let note = if reads.is_empty() { NoteRef::That } else { NoteRef::On(reads.clone()) };
+
+        let write = match self.top_intent {
+            Some(TopIntents::TakeNote) => Write::Note(reads, text),
+            Some(TopIntents::EditNote) => Write::EditNote(note, text),
+            Some(TopIntents::DeleteNote) => Write::DeleteNote(note),
+            _ => return None
+        };
+
+        Some(Intent::WriteIntent(write))
+    }
+
+    // "read my notes on this question", "list all notes"
+    fn create_note_read_intent(&mut self, entities: Vec<Entity>) -> Vec<Intent> {
+        let read = match self.top_intent {
+            Some(TopIntents::ListNotes) => NoteRead::All,
+            _ => NoteRead::On(self.parse_answer_reads(&entities))
+        };
+
+        vec![Intent::NoteIntent(read)]
+    }
+
+    // "choose B", "change my answer to D", "unselect C"
+    fn create_choice_intent(&mut self, entities: &[Entity]) -> Option<Intent> {
+        let labels: Vec<String> = entities.iter()
+            .filter(|entity| entity.entity_type() == EntityType::Choice)
+            .flat_map(|entity| entity.children().iter())
+            .map(|child| child.get_text().trim().to_string())
+            .collect();
+
+        let edit = match self.top_intent {
+            Some(TopIntents::AnswerChoose) => ChoiceEdit::Select(labels),
+            Some(TopIntents::AnswerChangeChoice) => ChoiceEdit::Change(labels),
+            Some(TopIntents::AnswerDeselect) => ChoiceEdit::Deselect(labels),
+            _ => return None
+        };
+
+        let reads = self.parse_answer_reads(entities);
+
+        Some(Intent::WriteIntent(Write::Choose(reads, edit)))
+    }
+
+    // reading back answers, "read my answer to question 4", "read the last sentence", "spell photosynthesis"
+    fn create_answer_read_intent(&mut self, entities: Vec<Entity>) -> Vec<Intent> {
+        let read = match self.top_intent {
+            Some(TopIntents::AnswerSpell) => AnswerRead::Spell(Self::text_value(&entities, EntityType::Text)),
+            // "what did I choose for question 12"
+            Some(TopIntents::ChoiceCheck) => AnswerRead::Choice(self.parse_answer_reads(&entities)),
+            _ if Self::has_entity(&entities, EntityType::TextUnit) => {
+                AnswerRead::Piece(Self::text_unit(&entities), Self::text_position(&entities))
+            },
+            _ if Self::has_entity(&entities, EntityType::Rough) => AnswerRead::RoughWork(self.parse_answer_reads(&entities)),
+            _ => AnswerRead::Whole(self.parse_answer_reads(&entities))
+        };
+
+        vec![Intent::AnswerIntent(read)]
+    }
+
+    // the locators among answer entities, naming the question answered
+    fn parse_answer_reads(&mut self, entities: &[Entity]) -> Vec<Read> {
+        let locators: Vec<Entity> = entities.iter()
+            .filter(|entity| !matches!(entity.entity_type(),
+                EntityType::Text | EntityType::TextUnit | EntityType::TextPosition
+                | EntityType::Choice | EntityType::Rough | EntityType::Note | EntityType::NoteText))
+            .cloned()
+            .collect();
+
+        self.parse_reads(&locators)
+    }
+
+    fn has_entity(entities: &[Entity], entity_type: EntityType) -> bool {
+        entities.iter().any(|entity| entity.entity_type() == entity_type)
+    }
+
+    // which of the readings offered was chosen, 0 when none is said
+    fn choice_number(text: &str) -> usize {
+        text.split_whitespace()
+            .find_map(|word| match word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase().as_str() {
+                "first" | "one" | "1" => Some(1),
+                "second" | "two" | "2" => Some(2),
+                _ => None
+            })
+            .unwrap_or(0)
+    }
+
+    // word, sentence or paragraph, a sentence when none is said
+    fn text_unit(entities: &[Entity]) -> TextUnit {
+        match Self::text_value(entities, EntityType::TextUnit).as_deref() {
+            Some("word") | Some("words") => TextUnit::Word,
+            Some("paragraph") | Some("paragraphs") => TextUnit::Paragraph,
+            _ => TextUnit::Sentence
+        }
+    }
+
+    // which piece of the answer, the last one when none is said
+    fn text_position(entities: &[Entity]) -> TextPosition {
+        match Self::text_value(entities, EntityType::TextPosition).as_deref() {
+            Some("first") | Some("start") | Some("beginning") => TextPosition::First,
+            Some("next") => TextPosition::Next,
+            Some("previous") => TextPosition::Previous,
+            Some("this") | Some("that") | Some("current") => TextPosition::Current,
+            _ => TextPosition::Last
+        }
+    }
+
+    // the lower cased value of the first entity of a type
+    fn text_value(entities: &[Entity], entity_type: EntityType) -> Option<String> {
+        entities.iter()
+            .find(|entity| entity.entity_type() == entity_type)
+            .and_then(|entity| entity.child().ok())
+            .map(|child| child.get_text().trim().to_lowercase())
+    }
+
+    // a bulk write over the questions of a scope, the question entity only names what is written to
+    fn create_bulk_write(&mut self, action: BulkAction, entities: &[Entity], mut filters: Vec<Filter>) -> Intent {
+        let mut scope = Vec::new();
+
+        for entity in entities {
+            match entity.entity_type() {
+                EntityType::All | EntityType::Question => continue,
+                _ => ()
+            }
+
+            if let Some(filter) = self.parse_filter(entity) {
+                filters.push(filter);
+                continue;
+            }
+
+            // an entity LU could not settle, such as one with several children, is left out
+            let reference = match self.get_reference(entity, 0) {
+                Ok(reference) => reference,
+                Err(_) => continue
+            };
+
+            scope.push(self.parse_read(entity, reference));
+        }
+
+        scope.sort_by_key(Self::scope_rank);
+
+        Intent::WriteIntent(Write::Bulk(action, scope, filters))
+    }
+
+    // parse the locator entities into reads ordered from the outermost scope inwards
+    fn parse_reads(&mut self, entities: &[Entity]) -> Vec<Read> {
+        let mut filters = Vec::new();
+        let mut reads = Vec::new();
+
+        for entity in entities {
+            if let Some(filter) = self.parse_filter(entity) {
+                filters.push(filter);
+                continue;
+            }
+
+            // an entity LU could not settle, such as one with several children, is left out
+            let reference = match self.get_reference(entity, 0) {
+                Ok(reference) => reference,
+                Err(_) => continue
+            };
+
+            reads.push(self.parse_read(entity, reference));
+        }
+
+        // state, section and text entities qualify the question looked for
+        if !filters.is_empty(){
+            let question = reads.iter().position(|read| matches!(read, Read::Question(_)));
+
+            if let Some(position) = question {
+                if let Read::Question(reference) = reads[position].clone() {
+                    // "next question in this section" keeps the question inside the current section
+                    reads[position] = if filters == [Filter::InSection] {
+                        Read::QuestionInSection(reference)
+                    }else{
+                        Read::QuestionWhere(reference, filters)
+                    };
+                }
+            }
+        }
+
+        reads.sort_by_key(Self::scope_rank);
+
+        reads
+    }
+
+    // entities that narrow the questions searched rather than locate a node
+    fn parse_filter(&self, entity: &Entity) -> Option<Filter> {
+        let filter = match entity.entity_type() {
+            EntityType::Marked => Filter::Marked,
+            EntityType::Skipped => Filter::Skipped,
+            EntityType::Unanswered => Filter::Unanswered,
+            EntityType::Visited => Filter::Visited,
+            EntityType::Noted => Filter::HasNote,
+            // "in this section" names the section without a child
+            EntityType::Section if !entity.has_children() => Filter::InSection,
+            EntityType::Contains => Filter::Contains(entity.child().ok()?.get_label()?),
+            EntityType::Rest => Filter::AfterCurrent,
+            _ => return None
+        };
+
+        Some(filter)
+    }
+
+    // how far out a read sits, pages and sections contain everything else
+    fn scope_rank(read: &Read) -> u8 {
+        match read {
+            Read::Page(_) => 0,
+            Read::Section(_) => 1,
+            Read::Question(_)
+            | Read::QuestionInSection(_)
+            | Read::QuestionWhere(_, _)
+            | Read::Passage(_) => 2,
+            Read::Paragraph(_) | Read::Figure(_) | Read::Table(_) => 3,
+            Read::TableRow(_) => 4,
+            Read::TableColumn(_) => 5,
+            Read::Scoped(_) => 6
+        }
     }
 
     // parse a read query
@@ -122,6 +529,13 @@ impl IntentParser {
         let entity = match entity.entity_type() {
             EntityType::Question => Read::Question(reference),
             EntityType::Section => Read::Section(reference),
+            EntityType::Page => Read::Page(reference),
+            EntityType::Passage => Read::Passage(reference),
+            EntityType::Paragraph => Read::Paragraph(reference),
+            EntityType::Figure => Read::Figure(reference),
+            EntityType::Table => Read::Table(reference),
+            EntityType::Row => Read::TableRow(reference),
+            EntityType::Column => Read::TableColumn(reference),
             _ => Read::Question(reference)
         };
 
@@ -129,10 +543,346 @@ impl IntentParser {
     }
 
     fn get_reference(&mut self, entity: &Entity, prev: u32) -> Result<Reference, &'static str> {
-        match entity.child(){
-            Ok(child) => Ok(child.get_reference(prev)),
-            Err(e) => Err(e)
-        } 
+        if let Some((from, to)) = entity.range() {
+            let from = self.child_reference(entity, &from, prev);
+            let to = self.child_reference(entity, &to, prev);
+
+            return Ok(Reference::Range(Box::new(from), Box::new(to)));
+        }
+
+        let child = entity.child()?;
+
+        Ok(self.child_reference(entity, &child, prev))
+    }
+
+    fn child_reference(&self, entity: &Entity, child: &EntityChild, prev: u32) -> Reference {
+        // "question 17" and "section B" are printed names, not counts from the start
+        match entity.entity_type() {
+            EntityType::Question | EntityType::Section if entity.has_children() && child.is_absolute() => {
+                Reference::Label(child.get_text())
+            },
+            _ => child.get_reference(prev)
+        }
+    }
+
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Vec<Intent> {
+        let response: LuResponse = serde_json::from_str(json).expect("the LU response should deserialize");
+
+        IntentParser::new().parse(response)
+    }
+
+    #[test]
+    fn three_children_are_left_out(){
+        let intents = parse(r#"{
+            "query": "mark questions 3, 4 and 5",
+            "top_intent": "mark_for_review",
+            "Entities": [
+                {"entity": "locator_marked"},
+                {"entity": "typeofnav_question", "CHILD": [
+                    {"entity": "question_number", "value": "3"},
+                    {"entity": "question_number", "value": "4"},
+                    {"entity": "question_number", "value": "5"}
+                ]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Mark(vec![]))]);
+    }
+
+    #[test]
+    fn bulk_write_leaves_out_three_children(){
+        let intents = parse(r#"{
+            "top_intent": "mark_for_review",
+            "Entities": [
+                {"entity": "locator_marked"},
+                {"entity": "locator_all"},
+                {"entity": "typeofnav_section", "CHILD": [
+                    {"entity": "section_number", "value": "A"},
+                    {"entity": "section_number", "value": "B"},
+                    {"entity": "section_number", "value": "C"}
+                ]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Bulk(BulkAction::Mark, vec![], vec![]))]);
+    }
+
+    #[test]
+    fn filters_qualify_the_question(){
+        let intents = parse(r#"{
+            "top_intent": "Navigation",
+            "Entities": [
+                {"entity": "locator_marked"},
+                {"entity": "typeofnav_question", "CHILD": [{"entity": "question_ordinal", "value": {"offset": 1, "relativeTo": "current"}}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::ReadIntent(Read::QuestionWhere(Reference::Current(1), vec![Filter::Marked]))]);
+    }
+
+    #[test]
+    fn chained_locators_read_as_one_scoped_read(){
+        let intents = parse(r#"{
+            "top_intent": "Navigation",
+            "Entities": [
+                {"entity": "typeofnav_question", "CHILD": [{"entity": "question_number", "value": "2"}]},
+                {"entity": "typeofnav_section", "CHILD": [{"entity": "section_number", "value": "B"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::ReadIntent(Read::Scoped(vec![
+            Read::Section(Reference::Label("B".to_string())),
+            Read::Question(Reference::Label("2".to_string()))
+        ]))]);
+    }
+
+    #[test]
+    fn two_children_make_a_range(){
+        let intents = parse(r#"{
+            "top_intent": "Navigation",
+            "Entities": [
+                {"entity": "typeofnav_question", "CHILD": [
+                    {"entity": "question_number", "value": "3"},
+                    {"entity": "question_number", "value": "5"}
+                ]}
+            ]
+        }"#);
+
+        let range = Reference::Range(Box::new(Reference::Label("3".to_string())), Box::new(Reference::Label("5".to_string())));
+
+        assert_eq!(intents, vec![Intent::ReadIntent(Read::Question(range))]);
+    }
+
+    #[test]
+    fn the_rest_of_a_section_is_a_bulk_write(){
+        let intents = parse(r#"{
+            "top_intent": "skip",
+            "Entities": [
+                {"entity": "locator_skipped"},
+                {"entity": "locator_rest"},
+                {"entity": "typeofnav_section", "CHILD": [{"entity": "section_number", "value": "B"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Bulk(
+            BulkAction::Skip,
+            vec![Read::Section(Reference::Label("B".to_string()))],
+            vec![Filter::AfterCurrent]
+        ))]);
+    }
+
+    #[test]
+    fn clear_all_marks(){
+        let intents = parse(r#"{"top_intent": "clear_marks", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Bulk(BulkAction::ClearMark, vec![], vec![Filter::Marked]))]);
+    }
+
+    #[test]
+    fn counts_name_the_kind_without_a_child(){
+        let intents = parse(r#"{
+            "top_intent": "count_check",
+            "Entities": [
+                {"entity": "typeofnav_question"},
+                {"entity": "locator_marked"},
+                {"entity": "typeofnav_section", "CHILD": [{"entity": "section_number", "value": "B"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::Meta(MetaIntent::Count(
+            CountTarget::Questions,
+            vec![Read::Section(Reference::Label("B".to_string()))],
+            vec![Filter::Marked]
+        ))]);
+    }
+
+    #[test]
+    fn dictation_is_the_answer_text_or_the_whole_query(){
+        let intents = parse(r#"{
+            "query": "write the mitochondria",
+            "top_intent": "dictation",
+            "Entities": [{"entity": "answer_text", "CHILD": [{"entity": "text", "value": "the mitochondria"}]}]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(vec![], AnswerEdit::Append("the mitochondria".to_string())))]);
+
+        let intents = parse(r#"{"query": "the cell wall is rigid", "top_intent": "dictation", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(vec![], AnswerEdit::Append("the cell wall is rigid".to_string())))]);
+    }
+
+    #[test]
+    fn answering_names_the_question(){
+        let intents = parse(r#"{
+            "top_intent": "answer_start",
+            "Entities": [{"entity": "typeofnav_question", "CHILD": [{"entity": "question_number", "value": "4"}]}]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(
+            vec![Read::Question(Reference::Label("4".to_string()))],
+            AnswerEdit::Start
+        ))]);
+    }
+
+    #[test]
+    fn answer_edits_name_a_piece_of_the_answer(){
+        let intents = parse(r#"{
+            "top_intent": "answer_delete",
+            "Entities": [
+                {"entity": "text_position", "CHILD": [{"entity": "position", "value": "First"}]},
+                {"entity": "text_unit", "CHILD": [{"entity": "unit", "value": "word"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(vec![], AnswerEdit::Delete(TextUnit::Word, TextPosition::First)))]);
+
+        let intents = parse(r#"{
+            "top_intent": "answer_replace_phrase",
+            "Entities": [
+                {"entity": "answer_text", "CHILD": [{"entity": "text", "value": "there"}]},
+                {"entity": "answer_text", "CHILD": [{"entity": "text", "value": "their"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(
+            vec![],
+            AnswerEdit::ReplacePhrase("there".to_string(), "their".to_string())
+        ))]);
+    }
+
+    #[test]
+    fn spelling_a_word(){
+        let intents = parse(r#"{
+            "top_intent": "answer_spell",
+            "Entities": [{"entity": "answer_text", "CHILD": [{"entity": "text", "value": "Photosynthesis"}]}]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::AnswerIntent(AnswerRead::Spell(Some("photosynthesis".to_string())))]);
+
+        let intents = parse(r#"{"top_intent": "answer_read", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::AnswerIntent(AnswerRead::Whole(vec![]))]);
+    }
+
+    #[test]
+    fn options_are_chosen_by_label(){
+        let intents = parse(r#"{
+            "top_intent": "answer_choose",
+            "Entities": [
+                {"entity": "option_label", "CHILD": [{"entity": "label", "value": "B"}, {"entity": "label", "value": "option d"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Choose(vec![], ChoiceEdit::Select(vec!["B".to_string(), "option d".to_string()])))]);
+    }
+
+    #[test]
+    fn maths_and_the_reading_chosen(){
+        let intents = parse(r#"{
+            "query": "maths x squared",
+            "top_intent": "answer_math",
+            "Entities": [{"entity": "answer_text", "CHILD": [{"entity": "text", "value": "x squared"}]}]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(vec![], AnswerEdit::Math("x squared".to_string())))]);
+
+        let intents = parse(r#"{"query": "the second one", "top_intent": "answer_confirm", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Answer(vec![], AnswerEdit::ConfirmMath(2)))]);
+    }
+
+    #[test]
+    fn rough_work_is_named_by_its_entity(){
+        let intents = parse(r#"{
+            "top_intent": "answer_start",
+            "Entities": [
+                {"entity": "typeofnav_rough"},
+                {"entity": "typeofnav_question", "CHILD": [{"entity": "question_number", "value": "4"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::RoughWork(
+            vec![Read::Question(Reference::Label("4".to_string()))],
+            AnswerEdit::Start
+        ))]);
+
+        let intents = parse(r#"{"top_intent": "answer_read", "Entities": [{"entity": "typeofnav_rough"}]}"#);
+
+        assert_eq!(intents, vec![Intent::AnswerIntent(AnswerRead::RoughWork(vec![]))]);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn notes_are_taken_read_and_changed(){
+        let intents = parse(r#"{
+            "query": "note on question 4 check the units",
+            "top_intent": "take_note",
+            "Entities": [
+                {"entity": "typeofnav_question", "CHILD": [{"entity": "question_number", "value": "4"}]},
+                {"entity": "note_text", "CHILD": [{"entity": "text", "value": "check the units"}]}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Note(
+            vec![Read::Question(Reference::Label("4".to_string()))],
+            "check the units".to_string()
+        ))]);
+
+        // without a locator the note meant is the one last taken or read
+        let intents = parse(r#"{"query": "delete that note", "top_intent": "delete_note", "Entities": [{"entity": "typeofnav_note"}]}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::DeleteNote(NoteRef::That))]);
+
+        let intents = parse(r#"{"query": "list all notes", "top_intent": "list_notes", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::NoteIntent(NoteRead::All)]);
+
+        let intents = parse(r#"{"query": "read my notes on this question", "top_intent": "read_notes", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::NoteIntent(NoteRead::On(vec![]))]);
+    }
+
+    #[test]
+    fn unmarking_unskipping_and_undo(){
+        let intents = parse(r#"{
+            "top_intent": "unmark",
+            "Entities": [
+                {"entity": "typeofnav_question", "CHILD": [{"entity": "question_number", "value": "5"}]},
+                {"entity": "locator_marked"}
+            ]
+        }"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Unmark(vec![Read::Question(Reference::Label("5".to_string()))]))]);
+
+        let intents = parse(r#"{"query": "I'm not skipping this after all", "top_intent": "unskip", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Unskip(vec![]))]);
+
+        let intents = parse(r#"{"top_intent": "answer_undo", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Undo)]);
+
+        let intents = parse(r#"{"top_intent": "redo", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::WriteIntent(Write::Redo)]);
+    }
+
+    #[test]
+    fn going_back_and_forward(){
+        let intents = parse(r#"{"query": "go back", "top_intent": "go_back", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::VisitIntent(Visit::Back)]);
+
+        let intents = parse(r#"{"query": "go forward", "top_intent": "go_forward", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::VisitIntent(Visit::Forward)]);
+
+        let intents = parse(r#"{"query": "where was I before", "top_intent": "where_before", "Entities": []}"#);
+
+        assert_eq!(intents, vec![Intent::VisitIntent(Visit::Before)]);
+    }
+}
@@ -29,7 +29,12 @@ impl IntentResolver{
     }
 
     pub async fn resolve_input(&mut self, input: &str) -> IntentResult {
-        let lu_response = self.resolver.get(input).await?;
+        let mut lu_response = self.resolver.get(input).await?;
+
+        // dictation needs the words as spoken
+        if lu_response.query.is_empty(){
+            lu_response.query = input.to_string();
+        }
 
         let response = self.parser.parse(lu_response);
 
@@ -40,12 +45,14 @@ impl IntentResolver{
 
 #[derive(Deserialize, Debug)]
 struct LuResponse {
+    #[serde(default)]
+    query: String,
     top_intent: TopIntents,
     #[serde(alias = "Entities")]
     entities: Vec<Entity>
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 struct Entity {
     entity: EntityType,
     #[serde(alias = "CHILD")]
@@ -58,10 +65,27 @@ impl Entity {
         self.entity
     }
 
+    /// An entity without a child only names a scope, as in "in this section"
+    pub fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
     fn has_more_than_one_child(&self) -> bool {
         self.children.len() > 1
     }
 
+    pub fn children(&self) -> &[EntityChild] {
+        &self.children
+    }
+
+    /// "questions 3 to 5" carries both ends of the range as children
+    pub fn range(&self) -> Option<(EntityChild, EntityChild)> {
+        match self.children.as_slice() {
+            [from, to] => Some((from.clone(), to.clone())),
+            _ => None
+        }
+    }
+
     pub fn child(&self) -> Result<EntityChild, &'static str> {
         if self.has_more_than_one_child(){
             return Err("Entity has more than one child");
@@ -125,7 +149,35 @@ impl EntityChild {
         val.parse::<i32>().unwrap()
     }
 
+    /// A plain value names something by its printed number or label rather than an offset
+    pub fn is_absolute(&self) -> bool {
+        !self.is_object()
+    }
+
+    /// The value as printed
+    pub fn get_text(&self) -> String {
+        self.value.to_string().replace('\"', "")
+    }
+
+    // a printed label such as "3b" that is not a plain offset
+    pub fn get_label(&self) -> Option<String> {
+        if self.is_object(){
+            return None;
+        }
+
+        let val = self.value.to_string().replace('\"', "");
+
+        match val.parse::<i32>() {
+            Ok(_) => None,
+            Err(_) => Some(val.trim().to_string())
+        }
+    }
+
     pub fn get_reference(&self, prev: u32) -> Reference {
+        if let Some(label) = self.get_label() {
+            return Reference::Label(label);
+        }
+
         let value = self.get_value();
 
         if prev > 0 {
@@ -162,11 +214,81 @@ pub enum TopIntents {
     #[serde(alias="skip")]
     Skip,
     #[serde(alias = "META")]
-    Meta
+    Meta,
+    #[serde(alias = "time_check")]
+    TimeCheck,
+    #[serde(alias = "marks_check")]
+    MarksCheck,
+    #[serde(alias = "clear_marks")]
+    ClearMarks,
+    #[serde(alias = "go_back")]
+    GoBack,
+    #[serde(alias = "go_forward")]
+    GoForward,
+    #[serde(alias = "where_before")]
+    WhereBefore,
+    #[serde(alias = "unmark")]
+    Unmark,
+    #[serde(alias = "unskip")]
+    Unskip,
+    #[serde(alias = "undo")]
+    Undo,
+    #[serde(alias = "redo")]
+    Redo,
+    #[serde(alias = "count_check")]
+    Count,
+    #[serde(alias = "answer_start")]
+    AnswerStart,
+    #[serde(alias = "dictation")]
+    Dictation,
+    #[serde(alias = "answer_replace")]
+    AnswerReplace,
+    #[serde(alias = "answer_clear")]
+    AnswerClear,
+    #[serde(alias = "answer_stop")]
+    AnswerStop,
+    #[serde(alias = "answer_insert")]
+    AnswerInsert,
+    #[serde(alias = "answer_new_paragraph")]
+    AnswerNewParagraph,
+    #[serde(alias = "answer_delete")]
+    AnswerDelete,
+    #[serde(alias = "answer_replace_phrase")]
+    AnswerReplacePhrase,
+    #[serde(alias = "answer_move")]
+    AnswerMove,
+    #[serde(alias = "answer_undo")]
+    AnswerUndo,
+    #[serde(alias = "answer_math")]
+    AnswerMath,
+    #[serde(alias = "answer_confirm")]
+    AnswerConfirm,
+    #[serde(alias = "answer_read")]
+    AnswerRead,
+    #[serde(alias = "answer_spell")]
+    AnswerSpell,
+    #[serde(alias = "answer_choose")]
+    AnswerChoose,
+    #[serde(alias = "answer_change_choice")]
+    AnswerChangeChoice,
+    #[serde(alias = "answer_deselect")]
+    AnswerDeselect,
+    #[serde(alias = "choice_check")]
+    ChoiceCheck,
+    #[serde(alias = "take_note")]
+    TakeNote,
+    #[serde(alias = "read_notes")]
+    ReadNotes,
+    #[serde(alias = "list_notes")]
+    ListNotes,
+    #[serde(alias = "edit_note")]
+    EditNote,
+    #[serde(alias = "delete_note")]
+    DeleteNote
 }
 
 /// Entity types
-#[derive(Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EntityType {
     #[serde(alias = "section_number")]
     #[serde(alias = "section_ordinal")]
@@ -176,28 +298,98 @@ pub enum EntityType {
     #[serde(alias = "section_number")]
     #[serde(alias = "typeofnav_question")]
     Question,
+    #[serde(alias = "page_number")]
+    #[serde(alias = "page_ordinal")]
+    #[serde(alias = "typeofnav_page")]
+    Page,
+    #[serde(alias = "passage_ordinal")]
+    #[serde(alias = "typeofnav_passage")]
+    Passage,
+    #[serde(alias = "paragraph_ordinal")]
+    #[serde(alias = "typeofnav_paragraph")]
+    Paragraph,
+    #[serde(alias = "figure_ordinal")]
+    #[serde(alias = "typeofnav_figure")]
+    Figure,
+    #[serde(alias = "table_ordinal")]
+    #[serde(alias = "typeofnav_table")]
+    Table,
+    #[serde(alias = "row_number")]
+    #[serde(alias = "row_ordinal")]
+    #[serde(alias = "typeofnav_row")]
+    Row,
+    #[serde(alias = "column_number")]
+    #[serde(alias = "column_ordinal")]
+    #[serde(alias = "typeofnav_column")]
+    Column,
     #[serde(alias = "locator_marked")]
     Marked,
     #[serde(alias = "locator_skipped")]
-    Skipped
+    Skipped,
+    #[serde(alias = "locator_unanswered")]
+    Unanswered,
+    #[serde(alias = "locator_visited")]
+    Visited,
+    #[serde(alias = "locator_noted")]
+    Noted,
+    #[serde(alias = "locator_text")]
+    Contains,
+    #[serde(alias = "locator_all")]
+    #[serde(alias = "quantifier_all")]
+    All,
+    #[serde(alias = "locator_rest")]
+    Rest,
+    #[serde(alias = "answer_text")]
+    Text,
+    /// the rough work for a question rather than its answer
+    #[serde(alias = "typeofnav_rough")]
+    Rough,
+    /// a note, "that note"
+    #[serde(alias = "typeofnav_note")]
+    Note,
+    /// what a note says
+    #[serde(alias = "note_text")]
+    NoteText,
+    /// word, sentence or paragraph of an answer
+    #[serde(alias = "text_unit")]
+    TextUnit,
+    /// first, last, this, next or previous piece of an answer
+    #[serde(alias = "text_position")]
+    TextPosition,
+    /// an option of a multiple choice question, "B and D" carries each label as a child
+    #[serde(alias = "option_label")]
+    Choice,
+    #[serde(alias = "status_attempted")]
+    #[serde(alias = "status_remaining")]
+    Progress,
+    #[serde(alias = "meta_paper")]
+    Paper,
+    #[serde(alias = "meta_time")]
+    Time,
+    #[serde(alias = "meta_marks")]
+    Marks
 }
 
 impl LuResponse {
 
     pub fn is_read(&self)  -> bool {
-        match self.top_intent {
-            TopIntents::Navigation
-            | TopIntents::BooleanPositionCheck 
-            => true,
-            _ => false
-        }
+        matches!(self.top_intent, TopIntents::Navigation | TopIntents::BooleanPositionCheck)
     }
 
     pub fn is_meta(&self) -> bool {
-        match self.top_intent {
-            TopIntents::Meta => true,
-            _ => false
-        }
+        matches!(self.top_intent, TopIntents::Meta | TopIntents::TimeCheck | TopIntents::MarksCheck | TopIntents::Count)
+    }
+
+    pub fn is_answer_read(&self) -> bool {
+        matches!(self.top_intent, TopIntents::AnswerRead | TopIntents::AnswerSpell | TopIntents::ChoiceCheck)
+    }
+
+    pub fn is_visit(&self) -> bool {
+        matches!(self.top_intent, TopIntents::GoBack | TopIntents::GoForward | TopIntents::WhereBefore)
+    }
+
+    pub fn is_note_read(&self) -> bool {
+        matches!(self.top_intent, TopIntents::ReadNotes | TopIntents::ListNotes)
     }
 
     pub fn top_intent(&self) -> TopIntents {
@@ -7,7 +7,7 @@ mod errors;
 
 pub use parser::interface::{Tag, Token::TagToken};
 use parser::{XmlContent, Sink, Tokenizer};
-pub use question_paper::{QPaperBuilder, Builder, QuestionPaper, Intent, IntentResult};
+pub use question_paper::{QPaperBuilder, Builder, QuestionPaper, Intent, IntentResult, PaperMetadata, Session};
 pub use intents::resolve;
 
 use std::sync::{Arc, RwLock};
@@ -16,10 +16,10 @@ use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use actix_web::{web, HttpRequest, HttpServer, get, post, HttpResponse, App, Responder};
 use futures::{StreamExt, TryStreamExt};
-use serde::Deserialize;
+use serde::Serialize;
 use std::time::SystemTime;
 
-use errors::Errors;
+pub use errors::Errors;
 // for keeping safe multihteadable state
 pub struct State ( Option<QuestionPaper> );
 
@@ -52,6 +52,13 @@ impl State {
         Err(Errors::InternalError("Could not resolve your request, try again".to_string()))
     }
 }
+/// Response to a successful upload
+#[derive(Debug, Serialize)]
+pub struct UploadResult {
+    pub bytes: usize,
+    pub metadata: PaperMetadata
+}
+
 // async function to resolve a user input
 pub async fn resolve_intent(state: web::Data<StateData>, input: &str) -> Result<IntentResult, Errors> {
     let now = SystemTime::now();
@@ -75,32 +82,40 @@ pub async fn resolve_intent(state: web::Data<StateData>, input: &str) -> Result<
     
 }
 // async function to handle network upload
-pub async fn upload(state: web::Data<StateData>, mut payload: Multipart) -> Result<String, Errors> {
+pub async fn upload(state: web::Data<StateData>, mut payload: Multipart) -> Result<UploadResult, Errors> {
     let mut content = String::new();
 
     // iterate over the multipart data 
     while let Ok(Some(mut field)) = payload.try_next().await {
-        let content_type = field.content_disposition().unwrap();
-        let _filename = content_type.get_filename().unwrap();
-
         while let Some(chunk) = field.next().await {
-            let data = chunk.unwrap();
-
-            content.push_str(&std::str::from_utf8(&data).unwrap().to_owned());
+            let data = match chunk {
+                Ok(data) => data,
+                Err(e) => return Err(Errors::InvalidInput(e.to_string()))
+            };
+
+            match std::str::from_utf8(&data) {
+                Ok(data) => content.push_str(data),
+                Err(_) => return Err(Errors::InvalidInput("The uploaded paper is not valid UTF-8".to_string()))
+            }
         }
     }
 
     // initialize the question paper with the content
-    let question_paper = parse_content(&content);
+    let question_paper = parse_content(&content)?;
+
+    let metadata = question_paper.metadata().clone();
 
     let mut state = state.write().unwrap();
 
     state.0 = Some(question_paper);
 
-    Ok(format!("uploaded {} bytes", content.len()))
+    Ok(UploadResult {
+        bytes: content.len(),
+        metadata
+    })
 }
 
-fn parse_content(content: &str) -> QuestionPaper{
+fn parse_content(content: &str) -> Result<QuestionPaper, Errors> {
     // create a new tokenizer and sink, start a thread to initialize all
     let (tx, rx) = mpsc::channel();
 
@@ -122,7 +137,7 @@ fn tokenize(content: &str, tx: Sender<Tag>) {
 }
 
 // a new thread to build a question paper
-fn build_question_paper(rx: Receiver<Tag>) -> QuestionPaper{
+fn build_question_paper(rx: Receiver<Tag>) -> Result<QuestionPaper, Errors> {
     let handle = thread::spawn(move || {
         let mut builder = QPaperBuilder::new();
 
@@ -133,5 +148,9 @@ fn build_question_paper(rx: Receiver<Tag>) -> QuestionPaper{
         builder.end()
     });
 
-    handle.join().unwrap()
+    match handle.join() {
+        Ok(Ok(question_paper)) => Ok(question_paper),
+        Ok(Err(errors)) => Err(Errors::BuildError(errors)),
+        Err(_) => Err(Errors::InternalError("The question paper could not be built, try again".to_string()))
+    }
 }
\ No newline at end of file
@@ -10,6 +10,7 @@ use actix_web::middleware::Logger;
 async fn upload_document(state: web::Data<StateData>, mut payload: Multipart) -> HttpResponse {
     match upload(state, payload).await {
         Ok(res) => HttpResponse::Ok().json(res),
+        Err(e) if e.is_client_error() => HttpResponse::BadRequest().json(e),
         Err(e) =>  HttpResponse::InternalServerError().json(e)
     }
 }
@@ -28,7 +28,9 @@ pub struct Tag {
     pub name: TagName,
     pub attributes: Vec<Attribute>,
     pub is_self_closing: bool,
-    pub value: Option<String>
+    pub value: Option<String>,
+    /// line the tag ends on, used to locate build errors
+    pub line: u64
 }
 
 
@@ -68,6 +70,12 @@ impl Tag {
     pub fn value(&self) -> Option<String> {
         self.value.clone()
     }
+    // get the value of an attribute
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        self.attributes.iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.clone())
+    }
     // check if it is an opening tag
     pub fn is_start_tag(&self) -> bool{
         self.kind == TagKind::StartTag
@@ -80,7 +88,7 @@ impl Tag {
     // section_number holds the section name so we can replace
     pub fn is_section_name(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "section_number"
         )
     }
@@ -89,49 +97,49 @@ impl Tag {
         let doc_tag = "xml";
         
         matches(
-            &self.name.get(),
+            self.name.get(),
             doc_tag
         )
     }
 
     pub fn is_root(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "root"
         )
     }
 
     pub fn is_question_number(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             r"^question_number$"
         )
     }
 
     pub fn is_section(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "SECTION_"
         )
     }
 
     pub fn is_question(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "^question$"
         )
     }
 
     pub fn is_item(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "item"
         )
     }
 
     pub fn is_instructions(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "instructions"
         )
     }
@@ -139,16 +147,177 @@ impl Tag {
     // meta_data
     pub fn is_meta(&self) -> bool {
         matches(
-            &self.name.get(),
+            self.name.get(),
             "meta_data"
         )
     }
 
+    // paper title inside meta_data
+    pub fn is_title(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^title$"
+        )
+    }
+
+    pub fn is_subject(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^subject$"
+        )
+    }
+
+    pub fn is_paper_code(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^paper_code$"
+        )
+    }
+
+    pub fn is_exam_board(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^exam_board$"
+        )
+    }
+
+    pub fn is_date(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^date$"
+        )
+    }
+
+    pub fn is_duration(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^duration$"
+        )
+    }
+
+    pub fn is_total_marks(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^total_marks$"
+        )
+    }
+
+    // question numbering scheme inside meta_data
+    pub fn is_numbering(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^numbering$"
+        )
+    }
+
+    pub fn is_language(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^(language|locale)$"
+        )
+    }
+
+    pub fn is_passage(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^passage$"
+        )
+    }
+
+    // the options of a multiple choice question
+    pub fn is_options(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^options$"
+        )
+    }
+
+    pub fn is_option(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^option$"
+        )
+    }
+
+    pub fn is_paragraph(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^p$"
+        )
+    }
+
+    // inline MathML, optionally namespaced
+    pub fn is_math(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^(m:)?math$"
+        )
+    }
+
+    pub fn is_figure(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^figure$"
+        )
+    }
+
+    pub fn is_image(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^(img|image)$"
+        )
+    }
+
+    // caption of a figure or table
+    pub fn is_caption(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^(caption|figcaption)$"
+        )
+    }
+
+    pub fn is_table(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^table$"
+        )
+    }
+
+    pub fn is_table_row(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^tr$"
+        )
+    }
+
+    pub fn is_table_header(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^th$"
+        )
+    }
+
+    pub fn is_table_cell(&self) -> bool {
+        matches(
+            self.name.get(),
+            r"^td$"
+        )
+    }
+
     // new page
     pub fn is_page(&self) -> bool {
         matches(
-            &self.name.get(),
-            r"^page_\d{1}$"
+            self.name.get(),
+            r"^page_\d+$"
         )
     }
+
+    // the number in a page tag name e.g. 12 for page_12
+    pub fn page_number(&self) -> Option<u32> {
+        if !self.is_page(){
+            return None;
+        }
+
+        self.name.get()["page_".len()..].parse().ok()
+    }
 }
\ No newline at end of file
@@ -110,8 +110,9 @@ impl Tokenizer {
         self.current_attr_name = name;
     }
 
+    // attribute values may arrive in several chunks e.g. alt text with spaces
     fn emit_attribute_value(&mut self, value: String){
-        self.current_attr_value = value;
+        self.current_attr_value.push_str(&value);
     }
 
     fn emit_attribute(&mut self){
@@ -174,7 +175,8 @@ impl Tokenizer {
             attributes: replace(&mut self.current_tag_attrs, vec![]),
             name,
             is_self_closing: self.current_tag_self_closing,
-            value
+            value,
+            line: self.current_line
         });
 
         self.current_tag_self_closing = false;
@@ -396,7 +398,7 @@ impl Tokenizer {
 
                 match pop_from_set!(self, input, set){
                     FromSet('"') | FromSet('\'') => go!(self: emit_attribute; to BeforeAttributeName),
-                    FromSet(' ') => go!(self: to BeforeAttributeName),
+                    FromSet(' ') => self.current_attr_value.push(' '),
                     NotFromSet(c) => self.emit_attribute_value(c),
                     _ => return ProcessResult::Suspend
                 }
@@ -0,0 +1,562 @@
+use std::borrow::Cow::{self, Borrowed};
+use serde::{Serialize, Deserialize};
+use super::math::MathNode;
+
+/// A candidate's answer to a question, kept in the session beside the node statuses.
+/// Edits happen at a cursor which moves by word, sentence or paragraph
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Answer {
+    pub text: String,
+    /// byte offset into the text
+    #[serde(default)]
+    pub cursor: usize,
+    /// maths dictated into the answer, the text holds its spoken form
+    #[serde(default)]
+    pub math: Vec<AnswerMath>
+}
+
+/// What the candidate is writing for a question, rough work is kept apart and not marked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Draft {
+    #[default]
+    Answer,
+    RoughWork
+}
+
+impl Draft {
+    /// How the draft is named in responses, e.g. "your rough work for question 4"
+    pub fn describe(&self, label: &str) -> String {
+        match self {
+            Draft::Answer => format!("your answer to question {}", label),
+            Draft::RoughWork => format!("your rough work for question {}", label)
+        }
+    }
+}
+
+/// A dictated maths expression kept as markup for visual and braille output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnswerMath {
+    pub spoken: String,
+    pub latex: String,
+    pub mathml: String
+}
+
+impl AnswerMath {
+    pub fn new(node: &MathNode) -> Self {
+        AnswerMath {
+            spoken: node.speak(),
+            latex: node.to_latex(),
+            mathml: node.to_mathml()
+        }
+    }
+}
+
+/// The pieces an answer can be navigated and edited by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextUnit {
+    Word,
+    Sentence,
+    Paragraph
+}
+
+/// Which piece of the answer a command refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextPosition {
+    First,
+    Last,
+    /// the piece at the cursor
+    Current,
+    Next,
+    Previous
+}
+
+impl TextUnit {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Word => "word",
+            Self::Sentence => "sentence",
+            Self::Paragraph => "paragraph"
+        }
+    }
+}
+
+impl Answer {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+
+    /// Add dictated text to the end of the answer
+    pub fn append(&mut self, text: &str) {
+        // formatted dictation may start with a paragraph break
+        let text = if self.is_empty() { text.trim() } else { text.trim_matches(' ') };
+
+        if text.is_empty(){
+            return;
+        }
+
+        // punctuation said after a finished sentence takes the place of its full stop
+        if text.starts_with(is_stop) {
+            while self.text.ends_with(is_stop) {
+                self.text.pop();
+            }
+        }
+
+        if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) && !joins_previous(text) {
+            self.text.push(' ');
+        }
+
+        self.text.push_str(text);
+        self.cursor = self.text.len();
+    }
+
+    /// Add dictated maths to the end of the answer
+    pub fn append_math(&mut self, math: AnswerMath) {
+        self.append(&math.spoken);
+        self.math.push(math);
+    }
+
+    /// Replace the whole answer
+    pub fn replace(&mut self, text: &str) {
+        self.text = text.trim().to_string();
+        self.cursor = self.text.len();
+        self.prune_math();
+    }
+
+    pub fn clear(&mut self) {
+        self.replace("");
+    }
+
+    /// Insert dictated text at the cursor
+    pub fn insert(&mut self, text: &str) -> Result<String, Cow<'static, str>> {
+        let text = text.trim_matches(' ');
+
+        if text.trim().is_empty(){
+            return Err(Borrowed("There was nothing to insert"));
+        }
+
+        let cursor = self.cursor.min(self.text.len());
+        let before = self.text[..cursor].ends_with(|c: char| !c.is_whitespace()) && !joins_previous(text);
+        let after = self.text[cursor..].starts_with(|c: char| !c.is_whitespace() && !is_closing(c))
+            && !text.ends_with(char::is_whitespace);
+
+        let inserted = format!(
+            "{}{}{}",
+            if before { " " } else { "" },
+            text,
+            if after { " " } else { "" }
+        );
+
+        self.text.insert_str(cursor, &inserted);
+        self.cursor = cursor + inserted.trim_end().len();
+
+        Ok(format!("Inserted \"{}\"", text.trim()))
+    }
+
+    /// Start a new paragraph at the cursor
+    pub fn insert_paragraph(&mut self) -> Result<String, Cow<'static, str>> {
+        let cursor = self.cursor.min(self.text.len());
+        let before = self.text[..cursor].trim_end().len();
+        let after = cursor + (self.text[cursor..].len() - self.text[cursor..].trim_start().len());
+
+        if before == 0 {
+            return Err(Borrowed("You are at the start of your answer"));
+        }
+
+        self.text.replace_range(before..after, "\n\n");
+        self.cursor = before + 2;
+
+        Ok("Started a new paragraph".to_string())
+    }
+
+    /// Remove a word, sentence or paragraph
+    pub fn delete(&mut self, unit: TextUnit, position: TextPosition) -> Result<String, Cow<'static, str>> {
+        let (start, end) = self.find(unit, position)?;
+        let deleted = self.text[start..end].to_string();
+
+        self.text.replace_range(start..end, "");
+        self.tidy(start);
+        self.prune_math();
+
+        Ok(format!("Deleted the {} \"{}\"", unit.name(), deleted))
+    }
+
+    /// Replace every whole word occurrence of a phrase, ignoring case
+    pub fn replace_phrase(&mut self, from: &str, to: &str) -> Result<String, Cow<'static, str>> {
+        let from = from.trim();
+        let to = to.trim();
+        let found = self.occurrences(from);
+
+        if from.is_empty() || found.is_empty() {
+            return Err(Cow::from(format!("\"{}\" is not in your answer", from)));
+        }
+
+        // replace from the back so earlier offsets stay valid
+        for &start in found.iter().rev() {
+            self.text.replace_range(start..start + from.len(), to);
+        }
+
+        self.tidy(self.cursor.min(self.text.len()));
+        self.prune_math();
+
+        match found.len() {
+            1 => Ok(format!("Replaced \"{}\" with \"{}\"", from, to)),
+            n => Ok(format!("Replaced {} occurrences of \"{}\" with \"{}\"", n, from, to))
+        }
+    }
+
+    /// Move the cursor to the start of a word, sentence or paragraph and say what is there
+    pub fn move_cursor(&mut self, unit: TextUnit, position: TextPosition) -> Result<String, Cow<'static, str>> {
+        let (start, end) = self.find(unit, position)?;
+
+        self.cursor = start;
+
+        Ok(format!("At the {} \"{}\"", unit.name(), &self.text[start..end]))
+    }
+
+    /// Whether text added at a byte offset starts a new sentence
+    pub fn starts_sentence(&self, at: usize) -> bool {
+        let before = self.text[..at.min(self.text.len())].trim_end_matches(' ');
+
+        before.is_empty() || before.ends_with(|c: char| is_terminal(c) || c == '\n')
+    }
+
+    /// Read a word, sentence or paragraph, moving the cursor to it
+    pub fn read(&mut self, unit: TextUnit, position: TextPosition) -> Result<String, Cow<'static, str>> {
+        let (start, end) = self.find(unit, position)?;
+
+        self.cursor = start;
+
+        Ok(self.text[start..end].to_string())
+    }
+
+    /// The whole answer as it is read out, paragraph breaks are announced
+    pub fn spoken(&self) -> String {
+        self.spans(TextUnit::Paragraph).iter()
+            .map(|&(start, end)| &self.text[start..end])
+            .collect::<Vec<_>>()
+            .join(" New paragraph. ")
+    }
+
+    /// The word at the cursor, or a word as it is written in the answer
+    pub fn word(&mut self, word: Option<&str>) -> Result<String, Cow<'static, str>> {
+        let word = match word {
+            // without the punctuation that ends a sentence
+            None => return self.read(TextUnit::Word, TextPosition::Current)
+                .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_string()),
+            Some(word) => word.trim()
+        };
+
+        match self.occurrences(word).first() {
+            Some(&start) => {
+                self.cursor = start;
+
+                Ok(self.text[start..start + word.len()].to_string())
+            },
+            None => Err(Cow::from(format!("\"{}\" is not in your answer", word)))
+        }
+    }
+
+    /// The byte ranges of the words, sentences or paragraphs in the answer
+    pub fn spans(&self, unit: TextUnit) -> Vec<(usize, usize)> {
+        match unit {
+            TextUnit::Word => Self::split(&self.text, |c, _| c.is_whitespace(), false),
+            // a sentence ends at a full stop, question or exclamation mark followed by a space, or at a line break
+            TextUnit::Sentence => Self::split(&self.text, |c, next| {
+                c == '\n' || (is_terminal(c) && next.map(char::is_whitespace).unwrap_or(true))
+            }, true),
+            TextUnit::Paragraph => Self::split(&self.text, |c, _| c == '\n', false)
+        }
+    }
+
+    /// The word, sentence or paragraph a command refers to
+    pub fn find(&self, unit: TextUnit, position: TextPosition) -> Result<(usize, usize), Cow<'static, str>> {
+        let spans = self.spans(unit);
+
+        if spans.is_empty(){
+            return Err(Borrowed("Your answer is empty"));
+        }
+
+        // the piece at the cursor, or the last one before it
+        let current = spans.iter()
+            .rposition(|&(start, _)| start <= self.cursor)
+            .unwrap_or(0);
+
+        let found = match position {
+            TextPosition::First => Some(0),
+            TextPosition::Last => Some(spans.len() - 1),
+            TextPosition::Current => Some(current),
+            TextPosition::Next => Some(current + 1).filter(|&next| next < spans.len()),
+            TextPosition::Previous => current.checked_sub(1)
+        };
+
+        match found {
+            Some(index) => Ok(spans[index]),
+            None => Err(Cow::from(format!("There is no {} {}", match position {
+                TextPosition::Next => "next",
+                _ => "previous"
+            }, unit.name())))
+        }
+    }
+
+    // split text into trimmed spans, the boundary character is kept when it ends a span
+    fn split<F: Fn(char, Option<char>) -> bool>(text: &str, is_boundary: F, keep_boundary: bool) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut start = None;
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, c)| c);
+
+            if is_boundary(c, next) {
+                if let Some(begin) = start.take() {
+                    let end = if keep_boundary && !c.is_whitespace() { position + c.len_utf8() } else { position };
+
+                    spans.push((begin, begin + text[begin..end].trim_end().len()));
+                }
+            }else if start.is_none() && !c.is_whitespace() {
+                start = Some(position);
+            }
+        }
+
+        if let Some(begin) = start {
+            spans.push((begin, begin + text[begin..].trim_end().len()));
+        }
+
+        spans
+    }
+
+    // whole word occurrences of a phrase, ignoring case
+    fn occurrences(&self, phrase: &str) -> Vec<usize> {
+        let text = &self.text;
+        let mut found = Vec::new();
+
+        if phrase.is_empty(){
+            return found;
+        }
+
+        for (start, _) in text.char_indices() {
+            let end = start + phrase.len();
+
+            if end > text.len() || !text.is_char_boundary(end) {
+                continue;
+            }
+
+            let before = text[..start].chars().next_back().map(char::is_alphanumeric).unwrap_or(false);
+            let after = text[end..].chars().next().map(char::is_alphanumeric).unwrap_or(false);
+
+            if !before && !after && text[start..end].eq_ignore_ascii_case(phrase) {
+                // occurrences do not overlap
+                if found.last().map(|&last| start >= last + phrase.len()).unwrap_or(true) {
+                    found.push(start);
+                }
+            }
+        }
+
+        found
+    }
+
+    // collapse the spaces left behind by an edit and keep the cursor inside the text
+    fn tidy(&mut self, cursor: usize) {
+        let mut tidied = String::with_capacity(self.text.len());
+        let mut new_cursor = None;
+
+        for (position, c) in self.text.char_indices() {
+            if position >= cursor && new_cursor.is_none() {
+                new_cursor = Some(tidied.len());
+            }
+
+            let previous = tidied.chars().next_back();
+
+            let skip = c == ' ' && matches!(previous, None | Some(' ') | Some('\n'));
+
+            // no space before punctuation or a line break
+            if (c == '\n' || is_terminal(c) || c == ',') && previous == Some(' ') {
+                tidied.pop();
+            }
+
+            if !skip {
+                tidied.push(c);
+            }
+        }
+
+        let tidied = tidied.trim_end().to_string();
+
+        self.cursor = new_cursor.unwrap_or(tidied.len()).min(tidied.len());
+        self.text = tidied;
+    }
+
+    // maths whose spoken form was edited out of the text is no longer part of the answer
+    fn prune_math(&mut self) {
+        let text = &self.text;
+
+        self.math.retain(|math| text.contains(&math.spoken));
+    }
+}
+
+fn is_terminal(c: char) -> bool {
+    c == '.' || c == '!' || c == '?'
+}
+
+// punctuation that follows the word before it without a space
+fn is_closing(c: char) -> bool {
+    is_terminal(c) || c == ',' || c == ';' || c == ':' || c == ')'
+}
+
+// closing punctuation that ends a sentence or a clause, a closing bracket is kept
+fn is_stop(c: char) -> bool {
+    is_closing(c) && c != ')'
+}
+
+// text that continues the word before it, such as a comma or a paragraph break
+fn joins_previous(text: &str) -> bool {
+    text.starts_with(|c: char| is_closing(c) || c == '\n')
+}
+
+/// Spell a word letter by letter, as in "P, capital H, hyphen"
+pub fn spell(word: &str) -> String {
+    word.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '-' => "hyphen".to_string(),
+            '\'' => "apostrophe".to_string(),
+            '.' => "full stop".to_string(),
+            ',' => "comma".to_string(),
+            c if c.is_uppercase() => format!("capital {}", c),
+            c => c.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(text: &str) -> Answer {
+        let mut answer = Answer::default();
+        answer.append(text);
+        answer
+    }
+
+    #[test]
+    fn append_spaces_words(){
+        let mut answer = answer("The answer is four.");
+
+        answer.append("It is even.");
+        answer.append(", twice");
+
+        assert_eq!(answer.text, "The answer is four. It is even, twice");
+        assert_eq!(answer.cursor, answer.text.len());
+        assert_eq!(answer.word_count(), 8);
+
+        answer.append(".");
+        assert_eq!(answer.text, "The answer is four. It is even, twice.");
+    }
+
+    #[test]
+    fn closing_brackets_are_kept(){
+        let mut answer = answer("It grows (slowly)");
+
+        answer.append(".");
+
+        assert_eq!(answer.text, "It grows (slowly).");
+    }
+
+    #[test]
+    fn insert_at_cursor(){
+        let mut answer = answer("Cells divide. They grow.");
+
+        answer.move_cursor(TextUnit::Sentence, TextPosition::Last).unwrap();
+        answer.insert("Then").unwrap();
+
+        assert_eq!(answer.text, "Cells divide. Then They grow.");
+        assert!(answer.insert("  ").is_err());
+    }
+
+    #[test]
+    fn delete_pieces(){
+        let mut answer = answer("Cells divide. They grow quickly.");
+
+        answer.delete(TextUnit::Word, TextPosition::First).unwrap();
+        assert_eq!(answer.text, "divide. They grow quickly.");
+
+        answer.delete(TextUnit::Sentence, TextPosition::Last).unwrap();
+        assert_eq!(answer.text, "divide.");
+    }
+
+    #[test]
+    fn paragraphs(){
+        let mut answer = answer("First part.");
+
+        answer.insert_paragraph().unwrap();
+        answer.insert("Second part.").unwrap();
+
+        assert_eq!(answer.text, "First part.\n\nSecond part.");
+        assert_eq!(answer.spans(TextUnit::Paragraph).len(), 2);
+    }
+
+    #[test]
+    fn replace_phrases(){
+        let mut answer = answer("There cat sat there. Thereafter it left.");
+
+        answer.replace_phrase("there", "their").unwrap();
+
+        assert_eq!(answer.text, "their cat sat their. Thereafter it left.");
+        assert!(answer.replace_phrase("dog", "cat").is_err());
+    }
+
+    #[test]
+    fn read_moves_through_sentences(){
+        let mut answer = answer("One. Two. Three.");
+
+        assert_eq!(answer.read(TextUnit::Sentence, TextPosition::First), Ok("One.".to_string()));
+        assert_eq!(answer.read(TextUnit::Sentence, TextPosition::Next), Ok("Two.".to_string()));
+        assert_eq!(answer.read(TextUnit::Sentence, TextPosition::Previous), Ok("One.".to_string()));
+        assert!(answer.read(TextUnit::Sentence, TextPosition::Previous).is_err());
+        assert_eq!(answer.read(TextUnit::Word, TextPosition::Last), Ok("Three.".to_string()));
+    }
+
+    #[test]
+    fn paragraph_breaks_are_spoken(){
+        let mut answer = answer("First part.");
+
+        answer.insert_paragraph().unwrap();
+        answer.insert("Second part.").unwrap();
+
+        assert_eq!(answer.spoken(), "First part. New paragraph. Second part.");
+    }
+
+    #[test]
+    fn words_are_spelt(){
+        let mut answer = answer("Don't use X-rays.");
+
+        assert_eq!(answer.word(Some("x-rays")), Ok("X-rays".to_string()));
+        assert_eq!(spell("X-rays"), "capital X, hyphen, r, a, y, s");
+        assert_eq!(spell("Don't"), "capital D, o, n, apostrophe, t");
+        assert_eq!(answer.word(Some("gamma")), Err(Cow::from("\"gamma\" is not in your answer")));
+
+        // with no word said, the word at the cursor without its full stop
+        answer.move_cursor(TextUnit::Word, TextPosition::Last).unwrap();
+        assert_eq!(answer.word(None), Ok("X-rays".to_string()));
+    }
+
+    #[test]
+    fn edits_drop_maths_no_longer_in_the_text(){
+        let mut answer = answer("So");
+
+        answer.append_math(AnswerMath {
+            spoken: "x squared".to_string(),
+            latex: "{x}^{2}".to_string(),
+            mathml: String::new()
+        });
+        assert_eq!(answer.text, "So x squared");
+        assert_eq!(answer.math.len(), 1);
+
+        answer.replace("So nothing");
+        assert!(answer.math.is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+use super::{Builder, Tag, NodeData, FigureData, FigureKind, BuildErrors};
+use super::section_builder::SectionItem;
+
+/// Builder for a figure block and the images inside it
+#[derive(Debug)]
+pub struct FigureBuilder {
+    figure: FigureData
+}
+
+impl FigureBuilder {
+    pub fn new() -> Self {
+        FigureBuilder {
+            figure: Self::empty(FigureKind::Figure, 1)
+        }
+    }
+
+    fn empty(kind: FigureKind, page_number: u32) -> FigureData {
+        FigureData {
+            kind,
+            caption: None,
+            alt_text: None,
+            source: None,
+            page_number
+        }
+    }
+
+    /// Start a new figure on the given page
+    pub fn start(&mut self, page_number: u32){
+        self.figure = Self::empty(FigureKind::Figure, page_number);
+    }
+
+    /// A standalone image outside of any figure block
+    pub fn image(tag: &Tag, page_number: u32) -> SectionItem {
+        let mut figure = Self::empty(FigureKind::Image, page_number);
+
+        figure.alt_text = tag.attribute("alt");
+        figure.source = tag.attribute("src");
+
+        SectionItem::new(NodeData::Figure(figure))
+    }
+}
+
+impl Builder for FigureBuilder {
+    type Item = SectionItem;
+
+    fn process_tag(&mut self, tag: Tag) {
+        if tag.is_image() && tag.is_start_tag(){
+            // the first image with alt text describes the figure
+            if self.figure.alt_text.is_none(){
+                self.figure.alt_text = tag.attribute("alt");
+            }
+
+            if self.figure.source.is_none(){
+                self.figure.source = tag.attribute("src");
+            }
+        }
+
+        if tag.is_caption() && tag.is_end_tag(){
+            self.figure.caption = tag.value().map(|caption| caption.trim().to_string());
+        }
+    }
+
+    fn end(&mut self) -> Result<Self::Item, BuildErrors> {
+        let page_number = self.figure.page_number;
+        let figure = std::mem::replace(&mut self.figure, Self::empty(FigureKind::Figure, page_number));
+
+        Ok(SectionItem::new(NodeData::Figure(figure)))
+    }
+}
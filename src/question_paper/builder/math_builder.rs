@@ -0,0 +1,179 @@
+use super::{Builder, Tag, MathExpression, MathFormat, BuildErrors};
+use crate::question_paper::math::MathNode;
+
+// an open MathML element and the nodes parsed inside it so far
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    children: Vec<MathNode>
+}
+
+/// Builder for an inline MathML block
+#[derive(Debug)]
+pub struct MathBuilder {
+    stack: Vec<Frame>,
+    // the parsed expression once the math element is closed
+    root: Option<MathNode>,
+    markup: String
+}
+
+impl MathBuilder {
+    pub fn new() -> Self {
+        MathBuilder {
+            stack: vec![],
+            root: None,
+            markup: String::new()
+        }
+    }
+
+    /// Start a math block from its opening tag
+    pub fn start(&mut self, tag: &Tag){
+        self.stack.clear();
+        self.root = None;
+        self.markup.clear();
+
+        self.push_markup(tag);
+        self.stack.push(Frame {
+            name: tag.name().get().to_string(),
+            children: vec![]
+        });
+    }
+
+    // keep the original markup for visual and braille output
+    fn push_markup(&mut self, tag: &Tag){
+        // text before the opening tag belongs to the question
+        if !self.stack.is_empty(){
+            if let Some(ref value) = tag.value {
+                self.markup.push_str(value);
+            }
+        }
+
+        let name = tag.name().get();
+
+        if tag.is_start_tag(){
+            self.markup.push('<');
+            self.markup.push_str(name);
+
+            for attribute in &tag.attributes {
+                self.markup.push_str(&format!(" {}=\"{}\"", attribute.name, attribute.value));
+            }
+
+            if tag.is_self_closing {
+                self.markup.push('/');
+            }
+
+            self.markup.push('>');
+        }else{
+            self.markup.push_str(&format!("</{}>", name));
+        }
+    }
+
+    // strip a namespace prefix such as m:mi
+    fn local_name(name: &str) -> &str {
+        match name.find(':') {
+            Some(index) => &name[index + 1..],
+            None => name
+        }
+    }
+
+    // turn a closed element into a math node
+    fn element(name: &str, text: Option<String>, mut children: Vec<MathNode>) -> MathNode {
+        let text = text.map(|text| text.trim().to_string()).unwrap_or_default();
+
+        // the first two children of a layout element
+        let pair = |children: &mut Vec<MathNode>| {
+            let mut iter = children.drain(..);
+            let first = iter.next().unwrap_or_else(|| MathNode::Row(vec![]));
+            let second = iter.next().unwrap_or_else(|| MathNode::Row(vec![]));
+
+            (Box::new(first), Box::new(second))
+        };
+
+        match Self::local_name(name) {
+            "mn" => MathNode::Number(text),
+            "mi" => MathNode::Identifier(text),
+            "mo" => MathNode::Operator(text),
+            "mtext" | "ms" => MathNode::Text(text),
+            "mfrac" => {
+                let (numerator, denominator) = pair(&mut children);
+                MathNode::Fraction(numerator, denominator)
+            },
+            "msup" => {
+                let (base, exponent) = pair(&mut children);
+                MathNode::Power(base, exponent)
+            },
+            "msub" => {
+                let (base, subscript) = pair(&mut children);
+                MathNode::Subscript(base, subscript)
+            },
+            "msubsup" => {
+                let exponent = if children.len() > 2 { children.pop() } else { None };
+                let (base, subscript) = pair(&mut children);
+                let base = MathNode::Subscript(base, subscript);
+
+                match exponent {
+                    Some(exponent) => MathNode::Power(Box::new(base), Box::new(exponent)),
+                    None => base
+                }
+            },
+            "msqrt" => MathNode::Root(Box::new(MathNode::row(children)), None),
+            "mroot" => {
+                let (radicand, index) = pair(&mut children);
+                MathNode::Root(radicand, Some(index))
+            },
+            "mfenced" => MathNode::Fenced(Box::new(MathNode::row(children))),
+            _ => MathNode::row(children)
+        }
+    }
+}
+
+impl Builder for MathBuilder {
+    type Item = MathExpression;
+
+    fn process_tag(&mut self, tag: Tag) {
+        self.push_markup(&tag);
+
+        if tag.is_start_tag() && !tag.is_self_closing {
+            self.stack.push(Frame {
+                name: tag.name().get().to_string(),
+                children: vec![]
+            });
+
+            return;
+        }
+
+        // a self closing element has no content
+        let (name, children) = if tag.is_self_closing {
+            (tag.name().get().to_string(), vec![])
+        }else{
+            match self.stack.pop() {
+                Some(frame) => (frame.name, frame.children),
+                None => return
+            }
+        };
+
+        let node = Self::element(&name, tag.value(), children);
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            // the math element itself has been closed
+            None => self.root = Some(node)
+        }
+    }
+
+    fn end(&mut self) -> Result<Self::Item, BuildErrors> {
+        // an unclosed block is spoken as far as it was parsed
+        let node = match self.root.take() {
+            Some(node) => node,
+            None => MathNode::row(self.stack.drain(..).flat_map(|frame| frame.children).collect())
+        };
+
+        self.stack.clear();
+
+        Ok(MathExpression {
+            format: MathFormat::MathML,
+            markup: std::mem::take(&mut self.markup),
+            spoken: node.speak()
+        })
+    }
+}
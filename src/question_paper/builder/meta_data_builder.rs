@@ -0,0 +1,189 @@
+use super::{Builder, Tag, PaperMetadata, Numbering, Locale, BuildError, BuildErrors};
+use std::borrow::Cow;
+use std::mem::take;
+
+/// Builder for the meta_data block
+#[derive(Debug)]
+pub struct MetaDataBuilder {
+    metadata: PaperMetadata,
+    errors: Vec<BuildError>
+}
+
+impl MetaDataBuilder {
+    pub fn new() -> Self {
+        MetaDataBuilder {
+            metadata: PaperMetadata::default(),
+            errors: vec![]
+        }
+    }
+
+    // parse a duration such as "1 hour 30 minutes", "1.5 hours" or "90 minutes" into minutes
+    fn parse_duration(duration: &str) -> Result<Option<u32>, Cow<'static, str>> {
+        let too_long = || Cow::from(format!("the duration {} is too long", duration.trim()));
+
+        let mut total: Option<u32> = None;
+        let mut value: Option<&str> = None;
+
+        for word in duration.split(|c: char| c.is_whitespace() || c == ',') {
+            let word = word.trim();
+
+            if word.is_empty() || word.eq_ignore_ascii_case("and") {
+                continue;
+            }
+
+            // split values like "90min" or "1.5h"
+            let number_len = word.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(word.len());
+            let (number, unit) = word.split_at(number_len);
+
+            if !number.is_empty() {
+                value = Some(number);
+            }
+
+            let unit = unit.to_lowercase();
+
+            let minutes_per_unit = if unit.starts_with('h') {
+                60
+            }else if unit.starts_with('m') {
+                1
+            }else{
+                continue;
+            };
+
+            if let Some(number) = value.take() {
+                let minutes = Self::minutes(number, minutes_per_unit)?.ok_or_else(too_long)?;
+
+                total = Some(total.unwrap_or(0).checked_add(minutes).ok_or_else(too_long)?);
+            }
+        }
+
+        // a bare number is taken as minutes
+        let bare = match value {
+            Some(number) => Some(Self::minutes(number, 1)?.ok_or_else(too_long)?),
+            None => None
+        };
+
+        match (total, bare) {
+            (Some(total), Some(minutes)) => total.checked_add(minutes).map(Some).ok_or_else(too_long),
+            (total, bare) => Ok(total.or(bare))
+        }
+    }
+
+    // the whole minutes in a number of hours or minutes such as "1.5", None when it is too large to hold
+    fn minutes(number: &str, minutes_per_unit: u32) -> Result<Option<u32>, Cow<'static, str>> {
+        let (whole, fraction) = match number.find('.') {
+            Some(point) => (&number[..point], &number[point + 1..]),
+            None => (number, "")
+        };
+
+        if fraction.contains('.') || (whole.is_empty() && fraction.is_empty()) {
+            return Err(Cow::from(format!("{} is not a number", number)));
+        }
+
+        let whole = match whole {
+            "" => 0,
+            whole => match whole.parse::<u32>() {
+                Ok(whole) => whole,
+                Err(_) => return Ok(None)
+            }
+        };
+
+        // parts of a minute are dropped, "1.5 hours" is 90 minutes
+        let fraction: String = fraction.chars().take(3).collect();
+        let part = match fraction.parse::<u32>() {
+            Ok(part) => part * minutes_per_unit / 10u32.pow(fraction.len() as u32),
+            Err(_) => 0
+        };
+
+        Ok(whole.checked_mul(minutes_per_unit).and_then(|minutes| minutes.checked_add(part)))
+    }
+}
+
+impl Builder for MetaDataBuilder {
+    type Item = PaperMetadata;
+
+    fn process_tag(&mut self, tag: Tag) {
+        if !tag.is_end_tag(){
+            return;
+        }
+
+        let value = match tag.value() {
+            Some(value) => value.trim().to_string(),
+            None => return
+        };
+
+        if tag.is_title(){
+            self.metadata.title = Some(value);
+        }else if tag.is_subject(){
+            self.metadata.subject = Some(value);
+        }else if tag.is_paper_code(){
+            self.metadata.paper_code = Some(value);
+        }else if tag.is_exam_board(){
+            self.metadata.exam_board = Some(value);
+        }else if tag.is_date(){
+            self.metadata.date = Some(value);
+        }else if tag.is_duration(){
+            match Self::parse_duration(&value) {
+                Ok(minutes) => self.metadata.duration_minutes = minutes,
+                Err(message) => self.errors.push(BuildError::at(&tag, message))
+            }
+
+            self.metadata.duration = Some(value);
+        }else if tag.is_total_marks(){
+            self.metadata.total_marks = value.parse().ok();
+        }else if tag.is_numbering(){
+            self.metadata.numbering = match value.to_lowercase().as_str() {
+                "section" | "per_section" | "per section" => Numbering::PerSection,
+                _ => Numbering::Continuous
+            };
+        }else if tag.is_language(){
+            self.metadata.locale = Locale::from_tag(&value);
+        }else if tag.is_instructions(){
+            self.metadata.instructions.push(value);
+        }
+    }
+
+    fn end(&mut self) -> Result<Self::Item, BuildErrors> {
+        let metadata = take(&mut self.metadata);
+
+        if !self.errors.is_empty(){
+            return Err(BuildErrors(take(&mut self.errors)));
+        }
+
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations(){
+        assert_eq!(MetaDataBuilder::parse_duration("1 hour 30 minutes"), Ok(Some(90)));
+        assert_eq!(MetaDataBuilder::parse_duration("90 minutes"), Ok(Some(90)));
+        assert_eq!(MetaDataBuilder::parse_duration("2h"), Ok(Some(120)));
+        assert_eq!(MetaDataBuilder::parse_duration("1 hour and 15 min"), Ok(Some(75)));
+        assert_eq!(MetaDataBuilder::parse_duration("2 hours, 5 mins"), Ok(Some(125)));
+        assert_eq!(MetaDataBuilder::parse_duration("45"), Ok(Some(45)));
+    }
+
+    #[test]
+    fn decimal_hours(){
+        assert_eq!(MetaDataBuilder::parse_duration("1.5 hours"), Ok(Some(90)));
+        assert_eq!(MetaDataBuilder::parse_duration("2.25h"), Ok(Some(135)));
+        assert_eq!(MetaDataBuilder::parse_duration(".5 hours"), Ok(Some(30)));
+        assert_eq!(MetaDataBuilder::parse_duration("1.5.2 hours"), Err(Cow::from("1.5.2 is not a number")));
+    }
+
+    #[test]
+    fn durations_too_long(){
+        assert_eq!(MetaDataBuilder::parse_duration("80000000 hours"), Err(Cow::from("the duration 80000000 hours is too long")));
+        assert_eq!(MetaDataBuilder::parse_duration("99999999999 minutes"), Err(Cow::from("the duration 99999999999 minutes is too long")));
+    }
+
+    #[test]
+    fn durations_without_numbers(){
+        assert_eq!(MetaDataBuilder::parse_duration(""), Ok(None));
+        assert_eq!(MetaDataBuilder::parse_duration("two hours"), Ok(None));
+    }
+}
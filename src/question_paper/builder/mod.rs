@@ -1,28 +1,44 @@
-pub use crate::{TagToken, Tag, parser::interface::TagName};
+pub use crate::{Tag, parser::interface::TagKind};
 use std::borrow::Cow::{self, Borrowed};
 use super::{interface, QuestionPaper};
 mod section_builder;
-use std::mem::replace;
+mod meta_data_builder;
+mod figure_builder;
+mod table_builder;
+mod math_builder;
+use std::mem::take;
+use std::collections::HashMap;
 
-use section_builder::SectionBuilder;
+use section_builder::{SectionBuilder, SectionItem};
+use meta_data_builder::MetaDataBuilder;
 
-pub use interface::{NodeData, Node, Builder, SectionData, QuestionData};
+pub use interface::{NodeData, Node, Builder, SectionData, QuestionData, PassageData, ParagraphData, Choices, ChoiceOption, PageData, PaperMetadata, Numbering};
+pub use super::dictation::Locale;
+pub use interface::{FigureData, FigureKind, TableData, TableRowData, TableCellData, MathExpression, MathFormat};
+pub use interface::{BuildError, BuildErrors};
 
 
 /// Builder modes controll how the builder interprets an input tag
 #[derive(Debug, Clone, Copy)]
 enum Modes {
     Root,
+    Meta,
     Section,
 }
 
 #[derive(Debug)]
 pub struct QPaperBuilder {
-    errors: Vec<Cow<'static, str>>,
+    errors: Vec<BuildError>,
     nodes: Vec<Node>,
     mode: Option<Modes>,
     instructions: Vec<String>,
     section_builder: SectionBuilder,
+    meta_data_builder: MetaDataBuilder,
+    metadata: PaperMetadata,
+    // page number to page node
+    pages: HashMap<u32, usize>,
+    // the section tag that has been opened but not yet closed
+    open_section: Option<Tag>,
     total_questions: u32
 }
 
@@ -35,6 +51,10 @@ impl QPaperBuilder {
             mode: None,
             instructions: vec![],
             section_builder: SectionBuilder::new(),
+            meta_data_builder: MetaDataBuilder::new(),
+            metadata: PaperMetadata::default(),
+            pages: HashMap::new(),
+            open_section: None,
             total_questions: 0
         }
     }
@@ -53,17 +73,35 @@ impl QPaperBuilder {
                     }
     
                 },
+                Modes::Meta => {
+                    // process the meta_data block
+                    if tag.is_meta(){
+                        if tag.is_end_tag(){
+                            match self.meta_data_builder.end() {
+                                Ok(metadata) => self.metadata = metadata,
+                                Err(errors) => self.errors.extend(errors.0)
+                            }
+
+                            self.section_builder.set_numbering(self.metadata.numbering);
+                            self.mode = Some(Modes::Root);
+                        }
+                    }else{
+                        self.meta_data_builder.process_tag(tag);
+                    }
+                },
                 Modes::Section => {
                     // process a section block
                     if tag.is_section(){
                         if tag.is_end_tag(){
-                            self.insert_section();
+                            self.open_section = None;
+                            self.insert_section(&tag);
+                        }else{
+                            self.open_section = Some(tag);
                         }
                     }else{
                         self.section_builder.process_tag(tag);
                     }
                 }
-                _ => ()
             }
         }
         
@@ -75,6 +113,8 @@ impl QPaperBuilder {
 
         let mode = if tag.is_root(){
             Some(Modes::Root)
+        }else if tag.is_meta(){
+            Some(Modes::Meta)
         }else if tag.is_section(){
             Some(Modes::Section)
         }else {
@@ -91,36 +131,36 @@ impl QPaperBuilder {
         let is_self_closing = match tag {
             Tag {
                 is_self_closing: true,
-                kind: StartTag,
+                kind: TagKind::StartTag,
                 ..
             } => true,
             _ => false
         };
 
         if is_self_closing {
-            self.emit_error(Borrowed("Document node cannot must be self closing"));
+            self.emit_error(BuildError::at(&tag, Borrowed("Document node cannot be self closing")));
         }else{
             // append a document node
             if tag.is_start_tag(){
-                self.append(NodeData::Document, None, None);
+                self.append(NodeData::Document, None);
             } 
         }
     }
 
 
     // handle errors
-    fn emit_error(&mut self, error: Cow<'static, str>){
+    fn emit_error(&mut self, error: BuildError){
         self.errors.push(error);
     }
 
-    // append a node into this nodes array
+    // append a node into this nodes array as the last child of its parent
     fn append(
         &mut self,
         data: NodeData,
-        parent: Option<usize>,
-        prev: Option<usize>
+        parent: Option<usize>
     ) -> usize {
         let index = self.nodes.len();
+        let prev = parent.and_then(|parent| self.nodes[parent].last_child);
 
         // insert a navigator node
         self.nodes.push(Node {
@@ -131,6 +171,7 @@ impl QPaperBuilder {
             next: None,
             first_child: None,
             last_child: None,
+            page: None
         });
 
         if let Some(parent) = parent {
@@ -165,12 +206,30 @@ impl Builder for QPaperBuilder {
         self.process_in_mode(tag);
     }
 
-    fn end(&mut self) -> Self::Item {
+    fn end(&mut self) -> Result<Self::Item, BuildErrors> {
+        if let Some(section) = self.open_section.take() {
+            let message = format!("{} is not closed", section.name().get());
+            self.emit_error(BuildError::at(&section, Cow::from(message)));
+        }
+
+        if self.nodes.is_empty(){
+            self.emit_error(BuildError::new(Borrowed("The document is empty")));
+        }else if self.total_questions == 0 && self.errors.is_empty(){
+            self.emit_error(BuildError::new(Borrowed("The paper has no questions")));
+        }
+
+        if !self.errors.is_empty(){
+            return Err(BuildErrors(take(&mut self.errors)));
+        }
+
         let total = self.nodes.len();
-        let nodes = replace(&mut self.nodes, vec![]);
+        let nodes = take(&mut self.nodes);
 
+        // instructions outside the meta_data block are still candidate instructions
+        let mut metadata = take(&mut self.metadata);
+        metadata.instructions.extend(take(&mut self.instructions));
 
-        QuestionPaper::new(nodes, total -1, self.total_questions)
+        Ok(QuestionPaper::new(nodes, total -1, self.total_questions, metadata))
     }
 }
 
@@ -187,21 +246,350 @@ impl QPaperBuilder {
     }
 
     // insert a section
-    fn insert_section(&mut self) {
-        let section = self.section_builder.end();
+    fn insert_section(&mut self, tag: &Tag) {
+        let section = match self.section_builder.end() {
+            Ok(section) => section,
+            Err(errors) => {
+                self.errors.extend(errors.0);
+                self.section_builder.clear();
+                return;
+            }
+        };
 
+        // sections hang off the document node
+        if self.nodes.is_empty(){
+            self.emit_error(BuildError::at(tag, Borrowed("The document has no xml declaration before its first section")));
+            self.append(NodeData::Document, None);
+        }
 
-        self.total_questions = section.total_questions;
+        self.total_questions += section.total_questions;
 
         // append the section and its children
-        let mut prev = None;
-
-        let parent = self.append(NodeData::Section(section.section), Some(0), None);
+        let parent = self.append(NodeData::Section(section.section), Some(0));
 
-        for question in section.questions{
-            prev = Some(self.append(NodeData::Question(question), Some(parent), prev));
+        for item in section.items {
+            self.append_item(item, parent);
         }
 
         self.section_builder.clear();
     }
-}
\ No newline at end of file
+
+    // append a section item and everything attached to it
+    fn append_item(&mut self, item: SectionItem, parent: usize) -> usize {
+        let page = item.data.page_number().map(|number| self.page_node(number));
+        let index = self.append(item.data, Some(parent));
+
+        if let Some(page) = page {
+            self.link_page(index, page);
+        }
+
+        for child in item.children {
+            self.append_item(child, index);
+        }
+
+        index
+    }
+
+    // find or create the page node for a printed page number
+    fn page_node(&mut self, page_number: u32) -> usize {
+        if let Some(&page) = self.pages.get(&page_number) {
+            return page;
+        }
+
+        // pages hang off the document so that they can span sections
+        let page = self.append(NodeData::Page(PageData::new(page_number)), Some(0));
+
+        self.pages.insert(page_number, page);
+
+        page
+    }
+
+    // link content to the page it is printed on
+    fn link_page(&mut self, index: usize, page: usize) {
+        let label = match self.nodes[index].data {
+            NodeData::Question(ref question) => Some(question.label.clone()),
+            _ => None
+        };
+        let is_passage = self.nodes[index].data.is_passage();
+
+        self.nodes[index].page = Some(page);
+
+        if let NodeData::Page(ref mut page) = self.nodes[page].data {
+            if let Some(label) = label {
+                page.questions.push(label);
+            }else if is_passage {
+                page.num_of_passages += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Tokenizer, Sink};
+    use std::sync::mpsc;
+
+    const SECTION: &str = r#"<SECTION_A>
+        <section_number>Section A</section_number>
+        <page_1><item><question_number>1</question_number><question>Add.</question></item></page_1>
+        </SECTION_A>"#;
+
+    fn build(body: &str) -> Result<QuestionPaper, BuildErrors> {
+        let paper = format!(r#"<?xml version="1.0"?><root>{}</root>"#, body);
+
+        let (tx, rx) = mpsc::channel();
+        Tokenizer::tokenize(vec![paper], Sink::new(tx));
+
+        let mut builder = QPaperBuilder::new();
+
+        for tag in rx {
+            builder.process_tag(tag);
+        }
+
+        builder.end()
+    }
+
+    #[test]
+    fn metadata(){
+        let paper = build(&format!(r#"<meta_data>
+            <title>Mathematics</title><subject>Algebra</subject><paper_code>4MA1</paper_code>
+            <exam_board>Edexcel</exam_board><date>12 June 2020</date><duration>1 hour 30 minutes</duration>
+            <total_marks>80</total_marks><instructions>Answer all questions.</instructions>
+            </meta_data>{}"#, SECTION)).unwrap();
+
+        let metadata = paper.metadata();
+
+        assert_eq!(metadata.duration_minutes, Some(90));
+        assert_eq!(metadata.total_marks, Some(80));
+        assert_eq!(metadata.instructions, vec!["Answer all questions.".to_string()]);
+        assert_eq!(metadata.describe(), "This is Mathematics, Algebra, paper 4MA1, set by Edexcel, sat on 12 June 2020");
+        assert_eq!(metadata.describe_duration(), "This exam lasts 1 hour and 30 minutes");
+        assert_eq!(metadata.describe_total_marks(), "This paper is out of 80 marks");
+    }
+
+    #[test]
+    fn duration_too_long(){
+        let errors = build(&format!("<meta_data><duration>90000000 hours</duration></meta_data>{}", SECTION)).unwrap_err();
+
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!(errors.0[0].message, "the duration 90000000 hours is too long");
+    }
+
+    fn question<'a>(paper: &'a QuestionPaper, label: &str) -> &'a QuestionData {
+        let index = *paper.labels.questions(label).first().expect("no question has the label");
+
+        match paper.nodes[index].data {
+            NodeData::Question(ref question) => question,
+            _ => panic!("{} is not a question", label)
+        }
+    }
+
+    #[test]
+    fn labels_from_question_numbers(){
+        let paper = build(r#"<SECTION_A>
+            <section_number>Section A</section_number>
+            <page_1>
+            <item><question_number>3a</question_number><question>Add.</question></item>
+            <item><question>Subtract.</question><question_number>3b</question_number></item>
+            <item><question>Divide.</question></item>
+            </page_1>
+            </SECTION_A>"#).unwrap();
+
+        assert_eq!(question(&paper, "3a").question_number, 1);
+        assert_eq!(question(&paper, "3b").question_number, 2);
+        // a question without a printed label is labelled by its ordinal
+        assert_eq!(question(&paper, "3").question_number, 3);
+    }
+
+    #[test]
+    fn numbering_restarts_in_each_section(){
+        let paper = build(r#"<meta_data><numbering>per section</numbering></meta_data>
+            <SECTION_A>
+            <section_number>Section A</section_number>
+            <page_1><item><question>Add.</question></item><item><question>Subtract.</question></item></page_1>
+            </SECTION_A>
+            <SECTION_B>
+            <section_number>Section B</section_number>
+            <page_2><item><question>Multiply.</question></item></page_2>
+            </SECTION_B>"#).unwrap();
+
+        assert_eq!(paper.labels.questions("1").len(), 2);
+        assert_eq!(paper.labels.questions("2").len(), 1);
+        assert!(paper.labels.questions("3").is_empty());
+    }
+
+    fn page(paper: &QuestionPaper, page_number: u32) -> (usize, &PageData) {
+        paper.nodes.iter()
+            .find_map(|node| match node.data {
+                NodeData::Page(ref page) if page.page_number == page_number => Some((node.index, page)),
+                _ => None
+            })
+            .expect("the page is not in the paper")
+    }
+
+    #[test]
+    fn pages_span_sections(){
+        let paper = build(r#"<SECTION_A>
+            <section_number>Section A</section_number>
+            <page_12>
+            <passage><p>Cells divide.</p></passage>
+            <item><question_number>1</question_number><question>Name a cell.</question></item>
+            </page_12>
+            </SECTION_A>
+            <SECTION_B>
+            <section_number>Section B</section_number>
+            <page_12><item><question_number>2</question_number><question>Name an organ.</question></item></page_12>
+            <page_13><item><question_number>3</question_number><question>Name a system.</question></item></page_13>
+            </SECTION_B>"#).unwrap();
+
+        let (twelve, data) = page(&paper, 12);
+
+        assert_eq!(data.questions, vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(data.num_of_passages, 1);
+        assert_eq!(paper.nodes[twelve].parent, Some(0));
+        assert_eq!(paper.nodes[paper.labels.questions("2")[0]].page, Some(twelve));
+
+        let (thirteen, data) = page(&paper, 13);
+
+        assert_eq!(data.questions, vec!["3".to_string()]);
+        assert_eq!(paper.nodes[paper.labels.questions("3")[0]].page, Some(thirteen));
+    }
+
+    #[test]
+    fn tables_and_figures_attach_to_their_question(){
+        let paper = build(r#"<SECTION_A>
+            <section_number>Section A</section_number>
+            <page_3>
+            <item><question_number>1</question_number><question>Use the table.</question>
+            <table><caption>Rainfall</caption>
+            <tr><th>Day</th><th>Rain</th></tr>
+            <tr><td>Monday</td><td>4</td></tr>
+            <tr><td>Tuesday</td><td>7</td></tr>
+            </table>
+            <figure><img src="cell.png" alt="A plant cell"/><caption>Figure 1</caption></figure>
+            </item>
+            </page_3>
+            </SECTION_A>"#).unwrap();
+
+        let first = paper.labels.questions("1")[0];
+        let children: Vec<&Node> = paper.nodes.iter().filter(|node| node.parent == Some(first)).collect();
+
+        assert_eq!(children.len(), 2);
+
+        match children[0].data {
+            NodeData::Table(ref table) => {
+                assert_eq!(table.caption, Some("Rainfall".to_string()));
+                assert_eq!(table.headers, vec!["Day".to_string(), "Rain".to_string()]);
+                assert_eq!((table.num_of_rows, table.num_of_columns, table.page_number), (2, 2, 3));
+            },
+            ref data => panic!("expected a table: {:?}", data)
+        }
+
+        match children[1].data {
+            NodeData::Figure(ref figure) => {
+                assert_eq!(figure.kind, FigureKind::Figure);
+                assert_eq!(figure.alt_text, Some("A plant cell".to_string()));
+                assert_eq!(figure.caption, Some("Figure 1".to_string()));
+            },
+            ref data => panic!("expected a figure: {:?}", data)
+        }
+
+        let cell = paper.nodes.iter()
+            .find_map(|node| match node.data {
+                NodeData::TableCell(ref cell) if cell.row == 2 && cell.column == 2 => Some(cell),
+                _ => None
+            })
+            .expect("the table has no second row");
+
+        assert_eq!(cell.header, Some("Rain".to_string()));
+        assert_eq!(cell.value, "7");
+    }
+
+    fn question_text(items: &str) -> QuestionData {
+        let paper = build(&format!(r#"<SECTION_A>
+            <section_number>Section A</section_number>
+            <page_1>{}</page_1>
+            </SECTION_A>"#, items)).unwrap();
+
+        question(&paper, "1").clone()
+    }
+
+    #[test]
+    fn currency_in_questions_is_not_maths(){
+        let question = question_text("<item><question_number>1</question_number><question>It costs $5 and $10.</question></item>");
+
+        assert_eq!(question.question, "It costs $5 and $10.");
+        assert!(question.math.is_empty());
+    }
+
+    #[test]
+    fn maths_in_questions_may_start_with_a_number(){
+        let question = question_text("<item><question_number>1</question_number><question>Solve $2x + 3 = 7$ for x.</question></item>");
+
+        assert_eq!(question.math.len(), 1);
+        assert_eq!(question.math[0].markup, "2x + 3 = 7");
+
+        let question = question_text("<item><question_number>1</question_number><question>Find $3^2$.</question></item>");
+
+        assert_eq!(question.math.len(), 1);
+        assert_eq!(question.math[0].markup, "3^2");
+        assert!(!question.question.contains('$'), "{}", question.question);
+    }
+
+    #[test]
+    fn errors_are_collected_with_their_lines(){
+        let errors = build(r#"
+            <SECTION_A>
+            <section_number></section_number>
+            <page_1>
+            <item><question_number>1</question_number><question></question></item>
+            <item><question_number>2</question_number><question>Add.</question></item>
+            </page_1>
+            </SECTION_A>"#).unwrap_err();
+
+        let errors: Vec<String> = errors.0.iter().map(|error| error.to_string()).collect();
+
+        assert_eq!(errors, vec![
+            "line 3: section_number is empty".to_string(),
+            "line 5: question 1 in an unnamed section has no text".to_string()
+        ]);
+    }
+
+    #[test]
+    fn papers_without_questions_or_closed_sections(){
+        let errors = build("").unwrap_err();
+
+        assert_eq!(errors.to_string(), "The paper has no questions");
+
+        let errors = build("<SECTION_A><section_number>Section A</section_number>").unwrap_err();
+
+        assert_eq!(errors.0.len(), 1);
+        assert!(errors.0[0].message.ends_with("is not closed"), "{}", errors);
+    }
+
+    #[test]
+    fn options(){
+        let question = question_text(r#"<item><question_number>1</question_number><question>Pick.</question>
+            <options multiple="true"><option>two</option><option label="x">three</option></options></item>"#);
+
+        let choices = question.choices.unwrap();
+
+        assert!(choices.multiple);
+        assert_eq!(choices.labels(), "A or x");
+        assert_eq!(choices.option("x").map(|option| option.text.as_str()), Some("three"));
+    }
+
+    #[test]
+    fn duplicate_and_empty_options(){
+        let errors = build(r#"<SECTION_A><section_number>Section A</section_number><page_1>
+            <item><question_number>1</question_number><question>Pick.</question>
+            <options><option label="A">one</option><option label="A">two</option><option></option></options></item>
+            </page_1></SECTION_A>"#).unwrap_err();
+
+        let errors = errors.to_string();
+
+        assert!(errors.contains("option A appears twice"), "{}", errors);
+        assert!(errors.contains("has no text"), "{}", errors);
+    }
+}
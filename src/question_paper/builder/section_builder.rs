@@ -1,28 +1,79 @@
-use super::{Builder, Tag, TagName, QuestionData, SectionData};
-use std::mem::replace;
+use super::{Builder, Tag, NodeData, QuestionData, SectionData, PassageData, ParagraphData, Choices, ChoiceOption, Numbering, MathExpression, MathFormat};
+use super::{BuildError, BuildErrors};
+use std::borrow::Cow::{self, Borrowed};
+use super::figure_builder::FigureBuilder;
+use super::table_builder::TableBuilder;
+use super::math_builder::MathBuilder;
+use crate::question_paper::math::latex;
+use std::mem::take;
 /// builder modes for a setion
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum Modes {
-    Question
+    Question,
+    Figure,
+    Table
 }
 
 #[derive(Debug)]
 pub struct Section {
     pub section: SectionData,
-    pub questions: Vec<QuestionData>,
+    /// questions and passages in document order
+    pub items: Vec<SectionItem>,
     pub total_questions: u32
 }
 
+/// A node in a section together with the nodes attached to it
+#[derive(Debug)]
+pub struct SectionItem {
+    pub data: NodeData,
+    pub children: Vec<SectionItem>
+}
+
+impl SectionItem {
+    pub fn new(data: NodeData) -> Self {
+        SectionItem {
+            data,
+            children: vec![]
+        }
+    }
+}
+
 /// Builder for a section block
 #[derive(Debug)]
 pub struct SectionBuilder {
     num_of_questions: u32,
     current_question: u32,
     mode: Option<Modes>,
+    // mode to return to once a figure or table is complete
+    prev_mode: Option<Modes>,
     current_page: u32,
-    last_page_name: Option<TagName>,
-    questions: Vec<QuestionData>,
-    current_section_name: String
+    items: Vec<SectionItem>,
+    // figures and tables seen in the current item before its question
+    attachments: Vec<SectionItem>,
+    figure_builder: FigureBuilder,
+    table_builder: TableBuilder,
+    math_builder: MathBuilder,
+    // text and maths of the question being read, questions may contain nested markup
+    in_question: bool,
+    in_math: bool,
+    question_text: String,
+    question_math: Vec<MathExpression>,
+    // paragraphs of the passage being read
+    in_passage: bool,
+    paragraphs: Vec<String>,
+    // options of the multiple choice question being read
+    in_options: bool,
+    choices: Choices,
+    option_label: Option<String>,
+    // options read before the question of the current item
+    pending_choices: Option<Choices>,
+    errors: Vec<BuildError>,
+    current_section_name: String,
+    numbering: Numbering,
+    // printed label read from a question_number tag in the current item
+    current_label: Option<String>,
+    // index of the question already pushed for the current item
+    item_question: Option<usize>
 }
 
 impl SectionBuilder {
@@ -31,40 +82,361 @@ impl SectionBuilder {
             num_of_questions: 0,
             current_question: 1,
             mode: None,
+            prev_mode: None,
             current_page: 1,
-            last_page_name: None,
-            questions: vec![],
-            current_section_name: String::new()
+            items: vec![],
+            attachments: vec![],
+            figure_builder: FigureBuilder::new(),
+            table_builder: TableBuilder::new(),
+            math_builder: MathBuilder::new(),
+            in_question: false,
+            in_math: false,
+            question_text: String::new(),
+            question_math: vec![],
+            in_passage: false,
+            paragraphs: vec![],
+            in_options: false,
+            choices: Choices::default(),
+            option_label: None,
+            pending_choices: None,
+            errors: vec![],
+            current_section_name: String::new(),
+            numbering: Numbering::default(),
+            current_label: None,
+            item_question: None
         }
     }
 
     pub fn clear(&mut self){
        self.mode = None;
+       self.prev_mode = None;
        self.num_of_questions = 0; 
-       self.questions.clear();
+       self.items.clear();
+       self.attachments.clear();
+       self.in_question = false;
+       self.in_math = false;
+       self.in_passage = false;
+       self.paragraphs.clear();
+       self.in_options = false;
+       self.pending_choices = None;
+       self.errors.clear();
+       self.current_label = None;
+       self.item_question = None;
+
+       if self.numbering == Numbering::PerSection {
+           self.current_question = 1;
+       }
+    }
+
+    pub fn set_numbering(&mut self, numbering: Numbering){
+        self.numbering = numbering;
+    }
+
+    // read the printed label of the current question
+    fn process_question_number(&mut self, tag: &Tag) {
+        if !tag.is_end_tag(){
+            return;
+        }
+
+        let label = match tag.value() {
+            Some(label) => label.trim().to_string(),
+            None => return
+        };
+
+        // the label may come after the question text in the same item
+        match self.item_question {
+            Some(index) => {
+                if let NodeData::Question(ref mut question) = self.items[index].data {
+                    question.label = label;
+                }
+            },
+            None => self.current_label = Some(label)
+        }
     }
 
 
+    // inline maths inside a question
+    fn process_math(&mut self, tag: Tag) {
+        let finished = tag.is_math() && tag.is_end_tag();
+
+        self.math_builder.process_tag(tag);
+
+        if finished {
+            self.in_math = false;
+
+            match self.math_builder.end() {
+                Ok(expression) => {
+                    // whitespace around inline markup is not kept by the tokenizer
+                    self.question_text.push_str(&format!(" {} ", expression.spoken));
+                    self.question_math.push(expression);
+                },
+                Err(errors) => self.errors.extend(errors.0)
+            }
+        }
+    }
+
+    // collect the text of a question, speaking any maths it contains
+    fn push_question_text(&mut self, tag: &Tag) {
+        if let Some(ref value) = tag.value {
+            self.question_text.push_str(value);
+        }
+    }
+
+    // speak LaTeX in the question text, keeping the original markup
+    fn finish_question_text(&mut self) -> String {
+        let text = take(&mut self.question_text);
+        let (spoken, found) = latex::replace_in_text(&text);
+
+        for (markup, node) in found {
+            self.question_math.push(MathExpression {
+                format: MathFormat::LaTeX,
+                markup,
+                spoken: node.speak()
+            });
+        }
+
+        spoken.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
     fn process_question(&mut self, tag: Tag) {
+        if tag.is_question() && tag.is_start_tag(){
+            self.in_question = true;
+            self.question_text.clear();
+            self.question_math.clear();
+            return;
+        }
+
+        if !self.in_question {
+            return;
+        }
+
+        self.push_question_text(&tag);
+
+        if tag.is_math() && tag.is_start_tag(){
+            self.in_math = true;
+            self.math_builder.start(&tag);
+            return;
+        }
+
         if tag.is_end_tag(){
             if tag.is_question(){
-                let question = tag.value().unwrap();
+                self.in_question = false;
+
+                let question = self.finish_question_text();
+                let math = take(&mut self.question_math);
+
+                if question.is_empty(){
+                    let message = format!("question {} in {} has no text", self.current_question, self.section_name());
+                    self.errors.push(BuildError::at(&tag, Cow::from(message)));
+                }
+
+                // fall back to the ordinal when the paper has no printed label
+                let label = self.current_label.take()
+                    .unwrap_or_else(|| self.current_question.to_string());
 
                 // create a question data
                 let question_data = QuestionData{
                     question,
+                    label,
+                    math,
+                    choices: self.pending_choices.take(),
                     page_number: self.current_page,
                     question_number: self.current_question,
                     ..QuestionData::default()
                 };
 
-                self.questions.push(question_data);
+                let mut item = SectionItem::new(NodeData::Question(question_data));
+                item.children = take(&mut self.attachments);
+
+                self.item_question = Some(self.items.len());
+                self.items.push(item);
                 self.current_question += 1;
                 self.num_of_questions += 1;
             }
         }
     }
 
+    fn process_passage(&mut self, tag: Tag) {
+        if tag.is_passage() && tag.is_start_tag(){
+            self.in_passage = true;
+            self.paragraphs.clear();
+            return;
+        }
+
+        if tag.is_paragraph() && tag.is_end_tag(){
+            if let Some(paragraph) = tag.value() {
+                self.paragraphs.push(paragraph.trim().to_string());
+            }
+            return;
+        }
+
+        if !(tag.is_passage() && tag.is_end_tag()){
+            return;
+        }
+
+        self.in_passage = false;
+
+        // a passage without paragraph markup is a single paragraph
+        let mut paragraphs = take(&mut self.paragraphs);
+
+        if let Some(text) = tag.value() {
+            if !text.trim().is_empty(){
+                paragraphs.push(text.trim().to_string());
+            }
+        }
+
+        if paragraphs.is_empty(){
+            return;
+        }
+
+        let mut item = SectionItem::new(NodeData::Passage(PassageData {
+            passage: paragraphs.join("\n\n"),
+            page_number: self.current_page
+        }));
+
+        item.children = paragraphs.into_iter()
+            .enumerate()
+            .map(|(position, text)| SectionItem::new(NodeData::Paragraph(ParagraphData {
+                paragraph: position as u32 + 1,
+                text
+            })))
+            .collect();
+
+        self.items.push(item);
+    }
+
+    // options are labelled A, B, C.. unless the paper prints its own labels
+    fn process_options(&mut self, tag: Tag) {
+        if tag.is_options() && tag.is_start_tag(){
+            let multiple = tag.attribute("multiple").map(|value| value == "true").unwrap_or(false)
+                || tag.attribute("type").map(|value| value == "multiple").unwrap_or(false);
+
+            self.in_options = true;
+            self.choices = Choices { multiple, options: vec![] };
+            return;
+        }
+
+        if tag.is_option(){
+            if tag.is_start_tag(){
+                self.option_label = tag.attribute("label").map(|label| label.trim().to_string());
+                return;
+            }
+
+            let label = self.option_label.take()
+                .unwrap_or_else(|| ((b'A' + (self.choices.options.len() % 26) as u8) as char).to_string());
+            let text = tag.value().map(|text| text.trim().to_string()).unwrap_or_default();
+
+            if text.is_empty(){
+                let message = format!("option {} in {} has no text", label, self.section_name());
+                self.errors.push(BuildError::at(&tag, Cow::from(message)));
+            }
+
+            if self.choices.option(&label).is_some(){
+                let message = format!("option {} appears twice in {}", label, self.section_name());
+                self.errors.push(BuildError::at(&tag, Cow::from(message)));
+            }
+
+            self.choices.options.push(ChoiceOption { label, text });
+            return;
+        }
+
+        if !(tag.is_options() && tag.is_end_tag()){
+            return;
+        }
+
+        self.in_options = false;
+
+        let choices = take(&mut self.choices);
+
+        if choices.options.is_empty(){
+            let message = format!("a multiple choice question in {} has no options", self.section_name());
+            return self.errors.push(BuildError::at(&tag, Cow::from(message)));
+        }
+
+        // the options usually follow the question text in the same item
+        match self.item_question {
+            Some(index) => {
+                if let NodeData::Question(ref mut question) = self.items[index].data {
+                    question.choices = Some(choices);
+                }
+            },
+            None => self.pending_choices = Some(choices)
+        }
+    }
+
+    // attach a figure or table to the question of the current item
+    fn attach(&mut self, attachment: SectionItem) {
+        match self.item_question {
+            Some(index) => self.items[index].children.push(attachment),
+            None => self.attachments.push(attachment)
+        }
+    }
+
+    // figures and tables in an item without a question stand on their own
+    fn flush_attachments(&mut self) {
+        let attachments = take(&mut self.attachments);
+
+        self.items.extend(attachments);
+    }
+
+    // enter a figure or table block
+    fn start_block(&mut self, mode: Modes) {
+        self.prev_mode = self.mode;
+        self.mode = Some(mode);
+
+        match mode {
+            Modes::Figure => self.figure_builder.start(self.current_page),
+            Modes::Table => self.table_builder.start(self.current_page),
+            _ => ()
+        }
+    }
+
+    // process a tag inside a figure or table block, the tag is handed back outside of one
+    fn process_block(&mut self, tag: Tag) -> Option<Tag> {
+        let mode = match self.mode {
+            Some(Modes::Figure) => Modes::Figure,
+            Some(Modes::Table) => Modes::Table,
+            _ => return Some(tag)
+        };
+
+        let finished = match mode {
+            Modes::Figure => tag.is_figure() && tag.is_end_tag(),
+            _ => tag.is_table() && tag.is_end_tag()
+        };
+
+        if !finished {
+            match mode {
+                Modes::Figure => self.figure_builder.process_tag(tag),
+                _ => self.table_builder.process_tag(tag)
+            }
+
+            return None;
+        }
+
+        let block = match mode {
+            Modes::Figure => self.figure_builder.end(),
+            _ => self.table_builder.end()
+        };
+
+        self.mode = self.prev_mode.take();
+
+        match block {
+            Ok(block) => self.attach(block),
+            Err(errors) => self.errors.extend(errors.0)
+        }
+
+        None
+    }
+
+    // name of the section for error messages
+    fn section_name(&self) -> String {
+        if self.current_section_name.is_empty(){
+            "an unnamed section".to_string()
+        }else{
+            self.current_section_name.clone()
+        }
+    }
+
     // insert a section meta tag
     fn get_section_data(&self) -> SectionData {
         SectionData {
@@ -73,7 +445,7 @@ impl SectionBuilder {
             num_of_marked: 0,
             num_of_skipped: 0,
             num_of_attempted: 0,
-            num_of_remaining: 0,
+            num_of_remaining: self.num_of_questions,
         }
     }
 }
@@ -82,40 +454,67 @@ impl Builder for SectionBuilder {
     type Item = Section;
 
     fn process_tag(&mut self, tag: Tag) {
+        if self.in_math {
+            return self.process_math(tag);
+        }
+
+        let tag = match self.process_block(tag) {
+            Some(tag) => tag,
+            None => return
+        };
+
         if tag.is_item(){
             if tag.is_start_tag(){
                 if tag.is_start_tag(){
                     self.mode = Some(Modes::Question);
+                    self.current_label = None;
+                    self.pending_choices = None;
+                    self.item_question = None;
                 }
+            }else{
+                self.flush_attachments();
             }
         }
 
-        // incrememnt the page
-        if tag.is_page(){
-            if tag.is_end_tag(){
-                // compare with the last page tagname
-                match self.last_page_name {
-                    Some(ref lp) => {
-                        if lp != tag.name(){
-                            self.current_page += 1;
-                            self.last_page_name = Some(tag.name().clone());
-                        }
-                    },
-                    _ => {
-                        self.current_page += 1;
-                        self.last_page_name = Some(tag.name().clone())
-                    }
-                }
-                
+        if tag.is_start_tag(){
+            if tag.is_figure(){
+                return self.start_block(Modes::Figure);
+            }else if tag.is_table(){
+                return self.start_block(Modes::Table);
+            }else if tag.is_image(){
+                let image = FigureBuilder::image(&tag, self.current_page);
+                return self.attach(image);
+            }
+        }
+
+        if tag.is_question_number(){
+            self.process_question_number(&tag);
+        }
+
+        // pages carry their printed number, content after a closed page is on the next one
+        if let Some(page_number) = tag.page_number() {
+            if tag.is_start_tag(){
+                self.current_page = page_number;
+            }else{
+                self.current_page = page_number + 1;
             }
         }
 
+        if tag.is_options() || self.in_options {
+            return self.process_options(tag);
+        }
+
+        if tag.is_passage() || self.in_passage {
+            return self.process_passage(tag);
+        }
+
         // set the section name
         if tag.is_section_name(){
             if tag.is_end_tag(){
-                let name = tag.value().unwrap();
-
-                self.current_section_name = name.clone();
+                match tag.value() {
+                    Some(name) => self.current_section_name = name.trim().to_string(),
+                    None => self.errors.push(BuildError::at(&tag, Borrowed("section_number is empty")))
+                }
             }
         }
 
@@ -131,11 +530,39 @@ impl Builder for SectionBuilder {
 
 
 
-    fn end(&mut self) -> Self::Item {
-        Section {
-            section: self.get_section_data(),
-            questions: replace(&mut self.questions, vec![]),
-            total_questions: self.current_question
+    fn end(&mut self) -> Result<Self::Item, BuildErrors> {
+        self.flush_attachments();
+
+        // blocks still open when the section closes
+        let unclosed = if self.in_math {
+            Some("a maths block")
+        }else if self.in_question {
+            Some("a question")
+        }else if self.in_passage {
+            Some("a passage")
+        }else if self.in_options {
+            Some("a list of options")
+        }else{
+            match self.mode {
+                Some(Modes::Figure) => Some("a figure"),
+                Some(Modes::Table) => Some("a table"),
+                _ => None
+            }
+        };
+
+        if let Some(block) = unclosed {
+            let message = format!("{} in {} is not closed", block, self.section_name());
+            self.errors.push(BuildError::new(Cow::from(message)));
         }
+
+        if !self.errors.is_empty(){
+            return Err(BuildErrors(take(&mut self.errors)));
+        }
+
+        Ok(Section {
+            section: self.get_section_data(),
+            items: take(&mut self.items),
+            total_questions: self.num_of_questions
+        })
     }
 }
@@ -0,0 +1,125 @@
+use super::{Builder, Tag, NodeData, TableData, TableRowData, TableCellData, BuildErrors};
+use super::section_builder::SectionItem;
+use std::mem::take;
+
+/// Builder for a table, rows and cells become child nodes of the table
+#[derive(Debug)]
+pub struct TableBuilder {
+    caption: Option<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    current_row: Vec<String>,
+    // true while every cell in the current row is a header cell
+    header_row: bool,
+    page_number: u32
+}
+
+impl TableBuilder {
+    pub fn new() -> Self {
+        TableBuilder {
+            caption: None,
+            headers: vec![],
+            rows: vec![],
+            current_row: vec![],
+            header_row: true,
+            page_number: 1
+        }
+    }
+
+    /// Start a new table on the given page
+    pub fn start(&mut self, page_number: u32){
+        *self = TableBuilder::new();
+        self.page_number = page_number;
+    }
+
+    fn cell_text(tag: &Tag) -> String {
+        tag.value().map(|value| value.trim().to_string()).unwrap_or_default()
+    }
+
+    fn end_row(&mut self){
+        let row = take(&mut self.current_row);
+
+        if row.is_empty(){
+            return;
+        }
+
+        // a leading row made only of header cells holds the column headers
+        if self.header_row && self.rows.is_empty() && self.headers.is_empty(){
+            self.headers = row;
+        }else{
+            self.rows.push(row);
+        }
+    }
+}
+
+impl Builder for TableBuilder {
+    type Item = SectionItem;
+
+    fn process_tag(&mut self, tag: Tag) {
+        if tag.is_table_row(){
+            if tag.is_start_tag(){
+                self.current_row.clear();
+                self.header_row = true;
+            }else{
+                self.end_row();
+            }
+        }else if tag.is_table_header() && tag.is_end_tag(){
+            self.current_row.push(Self::cell_text(&tag));
+        }else if tag.is_table_cell() && tag.is_end_tag(){
+            self.header_row = false;
+            self.current_row.push(Self::cell_text(&tag));
+        }else if tag.is_caption() && tag.is_end_tag(){
+            self.caption = tag.value().map(|caption| caption.trim().to_string());
+        }
+    }
+
+    fn end(&mut self) -> Result<Self::Item, BuildErrors> {
+        // a row left open at the end of the table
+        self.end_row();
+
+        let headers = take(&mut self.headers);
+        let rows = take(&mut self.rows);
+
+        let num_of_columns = rows.iter()
+            .map(|row| row.len())
+            .chain(std::iter::once(headers.len()))
+            .max()
+            .unwrap_or(0);
+
+        let children = rows.into_iter().enumerate().map(|(r, row)| {
+            let row_number = r as u32 + 1;
+
+            let cells: Vec<TableCellData> = row.into_iter().enumerate().map(|(c, value)| {
+                TableCellData {
+                    row: row_number,
+                    column: c as u32 + 1,
+                    header: headers.get(c).cloned(),
+                    value
+                }
+            }).collect();
+
+            let mut item = SectionItem::new(NodeData::TableRow(TableRowData {
+                row: row_number,
+                cells: cells.clone()
+            }));
+
+            item.children = cells.into_iter()
+                .map(|cell| SectionItem::new(NodeData::TableCell(cell)))
+                .collect();
+
+            item
+        }).collect::<Vec<_>>();
+
+        let mut table = SectionItem::new(NodeData::Table(TableData {
+            caption: self.caption.take(),
+            num_of_rows: children.len() as u32,
+            num_of_columns: num_of_columns as u32,
+            headers,
+            page_number: self.page_number
+        }));
+
+        table.children = children;
+
+        Ok(table)
+    }
+}
@@ -0,0 +1,282 @@
+use serde::Serialize;
+
+/// The spoken language of a paper, it decides which words are taken as dictation commands.
+/// "period" is a punctuation mark in American English but an ordinary word in British English
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Locale {
+    #[default]
+    EnGb,
+    EnUs
+}
+
+impl Locale {
+    /// Read a language tag such as "en-US", "en_GB" or "American English"
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag.trim().to_lowercase().replace('_', "-");
+
+        if tag == "en-us" || tag.contains("american") || tag.ends_with("-us") {
+            Locale::EnUs
+        }else{
+            Locale::EnGb
+        }
+    }
+
+    /// The word said before a command to dictate it as a word, as in "literal comma"
+    pub fn escape(&self) -> &'static str {
+        "literal"
+    }
+
+    // spoken commands, longer phrases are listed before the phrases they start with
+    fn commands(&self) -> &'static [(&'static str, Command)] {
+        match self {
+            Locale::EnGb => &[
+                ("full stop", Command::Close(".")),
+                ("question mark", Command::Close("?")),
+                ("exclamation mark", Command::Close("!")),
+                ("comma", Command::Close(",")),
+                ("semicolon", Command::Close(";")),
+                ("colon", Command::Close(":")),
+                ("dot dot dot", Command::Close("...")),
+                ("ellipsis", Command::Close("...")),
+                ("apostrophe", Command::Join("'")),
+                ("hyphen", Command::Join("-")),
+                ("dash", Command::Word("–")),
+                ("open inverted commas", Command::Open("'")),
+                ("close inverted commas", Command::Close("'")),
+                ("open quote", Command::Open("'")),
+                ("close quote", Command::Close("'")),
+                ("unquote", Command::Close("'")),
+                ("open bracket", Command::Open("(")),
+                ("close bracket", Command::Close(")")),
+                ("new paragraph", Command::NewParagraph),
+                ("new line", Command::NewLine),
+                ("capital", Command::Capital)
+            ],
+            Locale::EnUs => &[
+                ("period", Command::Close(".")),
+                ("full stop", Command::Close(".")),
+                ("question mark", Command::Close("?")),
+                ("exclamation point", Command::Close("!")),
+                ("exclamation mark", Command::Close("!")),
+                ("comma", Command::Close(",")),
+                ("semicolon", Command::Close(";")),
+                ("colon", Command::Close(":")),
+                ("dot dot dot", Command::Close("...")),
+                ("ellipsis", Command::Close("...")),
+                ("apostrophe", Command::Join("'")),
+                ("hyphen", Command::Join("-")),
+                ("dash", Command::Join("—")),
+                ("open quote", Command::Open("\"")),
+                ("close quote", Command::Close("\"")),
+                ("end quote", Command::Close("\"")),
+                ("unquote", Command::Close("\"")),
+                ("open parenthesis", Command::Open("(")),
+                ("close parenthesis", Command::Close(")")),
+                ("open paren", Command::Open("(")),
+                ("close paren", Command::Close(")")),
+                ("new paragraph", Command::NewParagraph),
+                ("new line", Command::NewLine),
+                ("capital", Command::Capital)
+            ]
+        }
+    }
+
+    // the command said at the start of the words and how many words it takes
+    fn command(&self, words: &[&str]) -> Option<(usize, Command)> {
+        self.commands().iter()
+            .find_map(|&(phrase, command)| {
+                let length = phrase.split(' ').count();
+
+                if words.len() < length {
+                    return None;
+                }
+
+                let said = words[..length].iter()
+                    .zip(phrase.split(' '))
+                    .all(|(word, expected)| word.eq_ignore_ascii_case(expected));
+
+                if said { Some((length, command)) } else { None }
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// punctuation that follows a word without a space
+    Close(&'static str),
+    /// punctuation that the next word follows without a space
+    Open(&'static str),
+    /// punctuation with no space on either side
+    Join(&'static str),
+    /// punctuation that stands apart like a word
+    Word(&'static str),
+    NewLine,
+    NewParagraph,
+    /// capitalise the next word
+    Capital
+}
+
+/// Turn dictation as it comes from speech recognition into answer text.
+/// Spoken punctuation and formatting commands are replaced and sentences are capitalised,
+/// `sentence_start` tells whether the text starts a sentence where it is added to the answer
+pub fn format(spoken: &str, locale: Locale, sentence_start: bool) -> String {
+    let words: Vec<&str> = spoken.split_whitespace().collect();
+    let mut text = Text { text: String::new(), capitalise: sentence_start, joined: false };
+    let mut position = 0;
+
+    while position < words.len() {
+        let word = words[position];
+
+        // "literal comma" is the word comma
+        if word.eq_ignore_ascii_case(locale.escape()) && position + 1 < words.len() {
+            text.word(words[position + 1]);
+            position += 2;
+            continue;
+        }
+
+        match locale.command(&words[position..]) {
+            Some((length, command)) => {
+                text.command(command);
+                position += length;
+            },
+            None => {
+                text.word(word);
+                position += 1;
+            }
+        }
+    }
+
+    text.text
+}
+
+// the formatted text with what the next word needs
+struct Text {
+    text: String,
+    capitalise: bool,
+    // the next word follows without a space
+    joined: bool
+}
+
+impl Text {
+    fn word(&mut self, word: &str) {
+        self.space();
+
+        // "i" is always a capital
+        let capitalise = self.capitalise || word == "i" || word.starts_with("i'");
+
+        match word.chars().next() {
+            Some(first) if capitalise => {
+                self.text.extend(first.to_uppercase());
+                self.text.push_str(&word[first.len_utf8()..]);
+            },
+            _ => self.text.push_str(word)
+        }
+
+        self.capitalise = false;
+        self.joined = false;
+    }
+
+    fn command(&mut self, command: Command) {
+        match command {
+            Command::Close(mark) => {
+                self.trim();
+                self.text.push_str(mark);
+                self.joined = false;
+
+                if mark == "." || mark == "?" || mark == "!" {
+                    self.capitalise = true;
+                }
+            },
+            Command::Open(mark) => {
+                self.space();
+                self.text.push_str(mark);
+                self.joined = true;
+            },
+            Command::Join(mark) => {
+                self.trim();
+                self.text.push_str(mark);
+                self.joined = true;
+            },
+            Command::Word(mark) => {
+                self.space();
+                self.text.push_str(mark);
+                self.joined = false;
+            },
+            Command::NewLine | Command::NewParagraph => {
+                self.trim();
+                self.text.push_str(if command == Command::NewLine { "\n" } else { "\n\n" });
+                self.capitalise = true;
+                self.joined = true;
+            },
+            Command::Capital => self.capitalise = true
+        }
+    }
+
+    // a space before the next word unless it starts the text or follows opening punctuation
+    fn space(&mut self) {
+        if !self.text.is_empty() && !self.joined {
+            self.text.push(' ');
+        }
+    }
+
+    fn trim(&mut self) {
+        let length = self.text.trim_end_matches(' ').len();
+
+        self.text.truncate(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn punctuation_and_capitals(){
+        assert_eq!(format("the cell divides full stop it grows", Locale::EnGb, true), "The cell divides. It grows");
+        assert_eq!(format("is it alive question mark", Locale::EnGb, true), "Is it alive?");
+        assert_eq!(format("and then comma i think", Locale::EnGb, false), "and then, I think");
+    }
+
+    #[test]
+    fn period_is_american(){
+        assert_eq!(format("a short period", Locale::EnGb, false), "a short period");
+        assert_eq!(format("it ended period", Locale::EnUs, true), "It ended.");
+    }
+
+    #[test]
+    fn quotes_and_dashes(){
+        assert_eq!(format("he said open quote yes close quote", Locale::EnGb, true), "He said 'yes'");
+        assert_eq!(format("he said open quote yes close quote", Locale::EnUs, true), "He said \"yes\"");
+        assert_eq!(format("cells dash all of them", Locale::EnGb, false), "cells – all of them");
+        assert_eq!(format("cells dash all of them", Locale::EnUs, false), "cells—all of them");
+    }
+
+    #[test]
+    fn brackets_and_joins(){
+        assert_eq!(format("water open bracket h two o close bracket", Locale::EnGb, false), "water (h two o)");
+        assert_eq!(format("well hyphen known", Locale::EnGb, false), "well-known");
+    }
+
+    #[test]
+    fn paragraphs(){
+        assert_eq!(format("first new paragraph second", Locale::EnGb, true), "First\n\nSecond");
+        assert_eq!(format("first new line second", Locale::EnGb, true), "First\nSecond");
+    }
+
+    #[test]
+    fn literal_words(){
+        assert_eq!(format("a literal comma is a mark", Locale::EnGb, false), "a comma is a mark");
+        assert_eq!(format("capital paris", Locale::EnGb, false), "Paris");
+        assert_eq!(format("capital paris", Locale::EnUs, false), "Paris");
+        assert_eq!(format("cap paris", Locale::EnUs, false), "cap paris");
+    }
+
+    #[test]
+    fn locales(){
+        assert_eq!(Locale::from_tag("en-US"), Locale::EnUs);
+        assert_eq!(Locale::from_tag("en_us"), Locale::EnUs);
+        assert_eq!(Locale::from_tag("American English"), Locale::EnUs);
+        assert_eq!(Locale::from_tag("en-GB"), Locale::EnGb);
+        assert_eq!(Locale::from_tag(""), Locale::EnGb);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use super::{Answer, Draft, Note, NodeStatus};
+
+// how many changes are kept for undo
+const HISTORY_LIMIT: usize = 50;
+
+/// The parts of the candidate's work a write can change, the statuses are kept without their visits
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub statuses: Vec<NodeStatus>,
+    pub notes: Vec<Note>,
+    pub answers: BTreeMap<usize, Answer>,
+    pub rough_work: BTreeMap<usize, Answer>,
+    pub answering: Option<usize>,
+    pub drafting: Draft
+}
+
+/// Session wide undo and redo over every write.
+/// Each change keeps the confirmation it was given so that undoing it can say what was reverted
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    // the work before each change, most recent last
+    undo: Vec<(String, Snapshot)>,
+    // the work before each undo, most recent last
+    redo: Vec<(String, Snapshot)>
+}
+
+impl History {
+    /// Keep the work as it was before a change, a new change can not be redone past
+    pub fn record(&mut self, change: String, before: Snapshot) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+
+        self.undo.push((change, before));
+        self.redo.clear();
+    }
+
+    /// The last change with the work before it, the current work is kept to redo it
+    pub fn undo(&mut self, current: Snapshot) -> Option<(String, Snapshot)> {
+        let (change, before) = self.undo.pop()?;
+
+        self.redo.push((change.clone(), current));
+
+        Some((change, before))
+    }
+
+    /// The last change undone with the work after it, the current work is kept to undo it again
+    pub fn redo(&mut self, current: Snapshot) -> Option<(String, Snapshot)> {
+        let (change, after) = self.redo.pop()?;
+
+        self.undo.push((change.clone(), current));
+
+        Some((change, after))
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(notes: usize) -> Snapshot {
+        Snapshot {
+            statuses: vec![],
+            notes: (0..notes).map(|index| Note { index, note: String::new() }).collect(),
+            answers: BTreeMap::new(),
+            rough_work: BTreeMap::new(),
+            answering: None,
+            drafting: Draft::Answer
+        }
+    }
+
+    #[test]
+    fn undo_then_redo(){
+        let mut history = History::default();
+
+        history.record("first".to_string(), snapshot(0));
+        history.record("second".to_string(), snapshot(1));
+
+        assert_eq!(history.undo(snapshot(2)), Some(("second".to_string(), snapshot(1))));
+        assert_eq!(history.redo(snapshot(1)), Some(("second".to_string(), snapshot(2))));
+        assert_eq!(history.redo(snapshot(2)), None);
+    }
+
+    #[test]
+    fn a_new_change_ends_redo(){
+        let mut history = History::default();
+
+        history.record("first".to_string(), snapshot(0));
+        history.undo(snapshot(1));
+        history.record("other".to_string(), snapshot(0));
+
+        assert_eq!(history.redo(snapshot(1)), None);
+    }
+
+    #[test]
+    fn oldest_changes_are_dropped(){
+        let mut history = History::default();
+
+        for change in 0..HISTORY_LIMIT + 5 {
+            history.record(change.to_string(), snapshot(change));
+        }
+
+        let mut undone = 0;
+
+        while let Some((change, _)) = history.undo(snapshot(0)) {
+            undone += 1;
+            assert!(change.parse::<usize>().unwrap() >= 5);
+        }
+
+        assert_eq!(undone, HISTORY_LIMIT);
+
+        history.clear();
+        assert_eq!(history.redo(snapshot(0)), None);
+    }
+}
@@ -1,28 +1,225 @@
 use super::{Node, Predicate, NodeData, Find};
+use super::interface::plural;
+use super::answer::{TextUnit, TextPosition, Draft};
 use std::borrow::Cow;
 use serde::Serialize;
 
 // intents interface
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Read {
     Question(Reference),
+    /// A question that does not leave the section being read
+    QuestionInSection(Reference),
+    /// A question among those that pass every filter
+    QuestionWhere(Reference, Vec<Filter>),
     Section(Reference),
+    Passage(Reference),
+    /// A paragraph of the passage being read
+    Paragraph(Reference),
+    /// Pages are addressed by their printed number
+    Page(Reference),
+    Figure(Reference),
+    Table(Reference),
+    /// A row of the table being read
+    TableRow(Reference),
+    /// A column of the table row being read
+    TableColumn(Reference),
+    /// Reads that each narrow the search to the node found by the one before, outermost first
+    Scoped(Vec<Read>),
+}
+
+impl Read {
+    /// The reference of a read, or of the innermost read of a scoped chain
+    pub fn reference(&self) -> Option<&Reference> {
+        match self {
+            Self::Question(ref reference)
+            | Self::QuestionInSection(ref reference)
+            | Self::QuestionWhere(ref reference, _)
+            | Self::Section(ref reference)
+            | Self::Passage(ref reference)
+            | Self::Paragraph(ref reference)
+            | Self::Page(ref reference)
+            | Self::Figure(ref reference)
+            | Self::Table(ref reference)
+            | Self::TableRow(ref reference)
+            | Self::TableColumn(ref reference) => Some(reference),
+            Self::Scoped(ref reads) => reads.last().and_then(|read| read.reference())
+        }
+    }
+
+    /// The same read with a different reference
+    pub fn with_reference(&self, reference: Reference) -> Read {
+        match self {
+            Self::Question(_) => Self::Question(reference),
+            Self::QuestionInSection(_) => Self::QuestionInSection(reference),
+            Self::QuestionWhere(_, ref filters) => Self::QuestionWhere(reference, filters.clone()),
+            Self::Section(_) => Self::Section(reference),
+            Self::Passage(_) => Self::Passage(reference),
+            Self::Paragraph(_) => Self::Paragraph(reference),
+            Self::Page(_) => Self::Page(reference),
+            Self::Figure(_) => Self::Figure(reference),
+            Self::Table(_) => Self::Table(reference),
+            Self::TableRow(_) => Self::TableRow(reference),
+            Self::TableColumn(_) => Self::TableColumn(reference),
+            Self::Scoped(ref reads) => {
+                let mut reads = reads.clone();
+
+                if let Some(last) = reads.pop() {
+                    reads.push(last.with_reference(reference));
+                }
+
+                Self::Scoped(reads)
+            }
+        }
+    }
+
+    /// Whether the read addresses a range of nodes
+    pub fn is_range(&self) -> bool {
+        matches!(self.reference(), Some(Reference::Range(_, _)))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Write {
     Mark(Vec<Read>),
     Skip(Vec<Read>),
-    Note(Vec<Read>, String)
+    /// Take questions out of review, the question being read when there are no reads
+    Unmark(Vec<Read>),
+    /// Take questions out of the skipped questions, the question being read when there are no reads
+    Unskip(Vec<Read>),
+    Note(Vec<Read>, String),
+    /// Change the text of a note, the latest one when a node has several
+    EditNote(NoteRef, String),
+    DeleteNote(NoteRef),
+    /// Apply an action to every question in a scope that passes the filters, the whole paper when the scope is empty
+    Bulk(BulkAction, Vec<Read>, Vec<Filter>),
+    /// Change the answer to a question, the question being answered when there are no reads.
+    /// Edits go to the rough work while the candidate is writing rough work
+    Answer(Vec<Read>, AnswerEdit),
+    /// Change the rough work for a question, starting it moves dictation to the rough work
+    RoughWork(Vec<Read>, AnswerEdit),
+    /// Choose options of a multiple choice question, the question being answered or read when there are no reads
+    Choose(Vec<Read>, ChoiceEdit),
+    /// Revert the last write, answer edits included
+    Undo,
+    /// Apply the last write undone again
+    Redo
 }
 
+impl Write {
+    /// Whether the write changes the candidate's work and so can be undone.
+    /// Starting and stopping answering and moving the cursor only change where the candidate is
+    pub fn changes_work(&self) -> bool {
+        !matches!(self,
+            Self::Answer(_, AnswerEdit::Start)
+            | Self::Answer(_, AnswerEdit::Stop)
+            | Self::Answer(_, AnswerEdit::MoveCursor(_, _))
+            | Self::RoughWork(_, AnswerEdit::Start)
+            | Self::RoughWork(_, AnswerEdit::Stop)
+            | Self::RoughWork(_, AnswerEdit::MoveCursor(_, _))
+            | Self::Undo
+            | Self::Redo)
+    }
+}
 
-/// Reference with their skip values
+/// Changes to the options chosen on a multiple choice question, options are named by their printed labels
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ChoiceEdit {
+    /// Choose options, a single answer question keeps only the latest choice
+    Select(Vec<String>),
+    /// Replace every option chosen so far
+    Change(Vec<String>),
+    Deselect(Vec<String>)
+}
+
+impl ChoiceEdit {
+    pub fn labels(&self) -> &Vec<String> {
+        match self {
+            Self::Select(ref labels) | Self::Change(ref labels) | Self::Deselect(ref labels) => labels
+        }
+    }
+}
+
+/// Changes to the answer of a question
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AnswerEdit {
+    /// Start answering, dictation goes to this question until answering stops
+    Start,
+    /// Add dictated text to the end of the answer
+    Append(String),
+    Replace(String),
+    Clear,
+    Stop,
+    /// Insert dictated text at the cursor
+    Insert(String),
+    InsertParagraph,
+    Delete(TextUnit, TextPosition),
+    /// Replace every occurrence of a phrase with another
+    ReplacePhrase(String, String),
+    MoveCursor(TextUnit, TextPosition),
+    /// Add spoken maths such as "x squared plus two x", an ambiguous expression is confirmed first
+    Math(String),
+    /// Choose between the readings of ambiguous maths, starting from 1
+    ConfirmMath(usize)
+}
+
+/// What a bulk write does to each question
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BulkAction {
+    Mark,
+    Skip,
+    ClearMark,
+    ClearSkip
+}
+
+impl BulkAction {
+    /// Confirm how many questions a bulk write changed
+    pub fn describe(&self, count: u32) -> String {
+        let done = match self {
+            Self::Mark => "marked for review",
+            Self::Skip => "skipped",
+            Self::ClearMark => "unmarked",
+            Self::ClearSkip => "unskipped"
+        };
+
+        match count {
+            0 => format!("No questions were {}", done),
+            1 => format!("1 question has been {}", done),
+            _ => format!("{} {} have been {}", count, plural(count, "question"), done)
+        }
+    }
+}
+
+
+/// Conditions on the state or content of the questions searched
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Filter {
+    Marked,
+    Skipped,
+    Unanswered,
+    Visited,
+    /// Printed on a page number
+    OnPage(u32),
+    /// Inside the section being read
+    InSection,
+    HasNote,
+    /// The text contains a phrase
+    Contains(String),
+    /// After the node being read, as in "the rest of this section"
+    AfterCurrent,
+    Not(Box<Filter>)
+}
+
+/// Reference with their skip values
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Reference {
     Start(i32),
     End(i32),
-    Current(i32)
+    Current(i32),
+    /// A printed question label such as "3b" or a section name such as "Section B"
+    Label(String),
+    /// Every node from one reference to another, both included
+    Range(Box<Reference>, Box<Reference>)
 }
 
 impl Reference {
@@ -30,7 +227,8 @@ impl Reference {
         let val = match self {
             Self::Start(c) => c,
             Self::Current(c) => c,
-            Self::End(c) => c
+            Self::End(c) => c,
+            Self::Label(_) | Self::Range(_, _) => return true
         };
 
         val >= &0 
@@ -41,28 +239,165 @@ impl Reference {
 pub enum Intent {
     ReadIntent(Read),
     WriteIntent(Write),
-    Meta(MetaIntent)
+    Meta(MetaIntent),
+    AnswerIntent(AnswerRead),
+    NoteIntent(NoteRead),
+    /// Move through the nodes in the order they were visited
+    VisitIntent(Visit)
+}
+
+/// Browser like history of the nodes visited, apart from offsets such as "previous question"
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Visit {
+    /// Go to the node visited before this one
+    Back,
+    /// Go to the node gone back from
+    Forward,
+    /// Say where the candidate was before, without going there
+    Before
+}
+
+/// Reading back notes
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NoteRead {
+    /// The notes on the nodes found, the current node when there are no reads
+    On(Vec<Read>),
+    /// Every note in the paper
+    All
+}
+
+/// The note an edit applies to
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NoteRef {
+    /// The note last taken or read, as in "delete that note"
+    That,
+    /// The notes on the nodes found, the current node when there are no reads
+    On(Vec<Read>)
+}
+
+/// Reading back the candidate's answers
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AnswerRead {
+    /// The whole answer to a question, the question being answered or read when there are no reads.
+    /// This is the rough work while the candidate is writing rough work
+    Whole(Vec<Read>),
+    /// The whole rough work for a question
+    RoughWork(Vec<Read>),
+    /// A word, sentence or paragraph of the current answer, the cursor moves to it
+    Piece(TextUnit, TextPosition),
+    /// Spell a word of the current answer letter by letter, the word at the cursor when none is given
+    Spell(Option<String>),
+    /// The options chosen on a multiple choice question
+    Choice(Vec<Read>)
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MetaIntent {
     Skipped,
-    Marked
+    Marked,
+    /// what paper is this
+    Paper,
+    /// how long is this exam
+    Duration,
+    /// how many marks is this paper out of
+    TotalMarks,
+    /// how far through the current section the candidate is
+    Progress,
+    /// how many of a kind of node are in a scope and pass the filters, the whole paper when the scope is empty
+    Count(CountTarget, Vec<Read>, Vec<Filter>)
+}
+
+/// The kinds of node that can be counted
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum CountTarget {
+    Questions,
+    Sections,
+    Pages,
+    Passages,
+    Figures,
+    Tables
+}
+
+impl CountTarget {
+    fn noun(&self) -> &'static str {
+        match self {
+            Self::Questions => "question",
+            Self::Sections => "section",
+            Self::Pages => "page",
+            Self::Passages => "passage",
+            Self::Figures => "figure",
+            Self::Tables => "table"
+        }
+    }
+}
+
+/// The answer to a counting query, left for the speech layer to phrase
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct CountResult {
+    pub target: CountTarget,
+    /// nodes that pass the filters
+    pub count: u32,
+    /// nodes of the kind in the scope, ignoring the filters
+    pub total: u32,
+    /// where the count was taken, such as "Section B" or "page 4"
+    pub scope: Option<String>
+}
+
+impl CountResult {
+    pub fn describe(&self) -> String {
+        let scope = match self.scope {
+            Some(ref scope) => format!(" in {}", scope),
+            None => String::new()
+        };
+
+        if self.count == self.total {
+            let noun = plural(self.count, self.target.noun());
+
+            format!("There {} {} {}{}", if self.count == 1 { "is" } else { "are" }, self.count, noun, scope)
+        }else{
+            // "0 of 1 question", the noun goes with the total
+            let noun = plural(self.total, self.target.noun());
+
+            format!("{} of {} {}{}", self.count, self.total, noun, scope)
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub enum WriteResult {
     Success(String),
-    Error(Cow<'static, str>)
+    Error(Cow<'static, str>),
+    /// Nothing was written, the candidate is asked to choose what they meant
+    Confirm(String)
 }
 
 pub type ReadResult = Result<Node, Cow<'static, str>>;
 
+/// Nodes of a range in document order
+pub type ReadListResult = Result<Vec<Node>, Cow<'static, str>>;
+
 #[derive(Debug, Clone, Serialize)]
 pub enum IntentResult {
     Read(Result<NodeData, Cow<'static, str>>),
+    Count(Result<CountResult, Cow<'static, str>>),
+    /// The nodes of a range read in document order
+    ReadList(Result<Vec<NodeData>, Cow<'static, str>>),
     Write(WriteResult),
-    Meta(String)
+    Meta(String),
+    /// Answer text to be read out
+    Answer(Result<String, Cow<'static, str>>),
+    /// Notes to be read out
+    Notes(Result<String, Cow<'static, str>>)
+}
+
+/// Types that can read back the candidate's answers
+pub trait AnswerReader {
+    fn read_answer(&mut self, read: &AnswerRead) -> Result<String, Cow<'static, str>>;
+}
+
+/// Types that can read back notes
+pub trait NoteReader {
+    fn read_notes(&mut self, read: &NoteRead) -> Result<String, Cow<'static, str>>;
 }
 
 /// Types that can be used to resolve read intents
@@ -72,9 +407,36 @@ pub trait Reader {
     /// Resolve a question
     fn resolve_question(&mut self, reference: &Reference) -> ReadResult;
 
+    /// Resolve a question within the current section
+    fn resolve_question_in_section(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a question that passes a set of filters
+    fn resolve_filtered_question(&mut self, reference: &Reference, filters: &[Filter]) -> ReadResult;
+
     /// Resolve a section
     fn resolve_section(&mut self, reference: &Reference) -> ReadResult;
 
+    /// Resolve a passage
+    fn resolve_passage(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a paragraph in the passage being read
+    fn resolve_paragraph(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a page
+    fn resolve_page(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a figure or an image
+    fn resolve_figure(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a table
+    fn resolve_table(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a row in the current table
+    fn resolve_table_row(&mut self, reference: &Reference) -> ReadResult;
+
+    /// Resolve a cell in the current table row
+    fn resolve_table_column(&mut self, reference: &Reference) -> ReadResult;
+
     /// Resolve from a reference
     fn resolve_referece<P: Predicate>(&mut self, reference: &Reference, predicate: P) -> ReadResult;
 
@@ -94,12 +456,38 @@ pub trait Writer {
     fn resolve_write_intent(&mut self, write_intent: &Write) ->  WriteResult;
 
     // process a read intent and mark it for review
-    fn mark_for_review(&mut self, read_intent: &Vec<Read>) -> WriteResult;
+    fn mark_for_review(&mut self, read_intent: &[Read]) -> WriteResult;
 
     /// Skip the component found on the read intent position
-    fn skip(&mut self, read_intent: &Vec<Read>) -> WriteResult;
+    fn skip(&mut self, read_intent: &[Read]) -> WriteResult;
+
+    /// Take the questions found out of review
+    fn unmark(&mut self, read_intent: &[Read]) -> WriteResult;
+
+    /// Take the questions found out of the skipped questions
+    fn unskip(&mut self, read_intent: &[Read]) -> WriteResult;
 
     /// take a note on the component found
-    fn note(&mut self, read_intent: &Vec<Read>, note: String) -> WriteResult;
+    fn note(&mut self, read_intent: &[Read], note: String) -> WriteResult;
+
+    /// Change the text of a note
+    fn edit_note(&mut self, note: &NoteRef, text: String) -> WriteResult;
+
+    fn delete_note(&mut self, note: &NoteRef) -> WriteResult;
+
+    /// Apply a write to every matching question in a scope
+    fn bulk_write(&mut self, action: BulkAction, scope: &[Read], filters: &[Filter]) -> WriteResult;
+
+    /// Change the answer or rough work for a question
+    fn answer(&mut self, draft: Draft, read_intent: &[Read], edit: &AnswerEdit) -> WriteResult;
+
+    /// Change the options chosen on a multiple choice question
+    fn choose(&mut self, read_intent: &[Read], edit: &ChoiceEdit) -> WriteResult;
+
+    /// Revert the last write, saying what was reverted
+    fn undo(&mut self) -> WriteResult;
+
+    /// Apply the last write undone again
+    fn redo(&mut self) -> WriteResult;
 
 }
\ No newline at end of file
@@ -1,7 +1,11 @@
 use crate::Tag;
 use super::QuestionPaper;
-use std::borrow::Cow::{self, Borrowed};
-use serde::Serialize;
+use super::dictation::Locale;
+use std::borrow::Cow;
+use std::fmt;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
 
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -9,21 +13,72 @@ pub enum NodeData {
     Document,
     Section(SectionData),
     Question(QuestionData),
+    Passage(PassageData),
+    Paragraph(ParagraphData),
+    Page(PageData),
+    Figure(FigureData),
+    Table(TableData),
+    TableRow(TableRowData),
+    TableCell(TableCellData),
     Instruction(String)
 }
 
 impl NodeData {
     pub fn is_question(&self) -> bool {
+        matches!(self, Self::Question(_))
+    }
+
+    pub fn is_section(&self) -> bool {
+        matches!(self, Self::Section(_))
+    }
+
+    pub fn is_passage(&self) -> bool {
+        matches!(self, Self::Passage(_))
+    }
+
+    pub fn is_paragraph(&self) -> bool {
+        matches!(self, Self::Paragraph(_))
+    }
+
+    pub fn is_page(&self) -> bool {
+        matches!(self, Self::Page(_))
+    }
+
+    pub fn is_figure(&self) -> bool {
+        matches!(self, Self::Figure(_))
+    }
+
+    pub fn is_table(&self) -> bool {
+        matches!(self, Self::Table(_))
+    }
+
+    /// The printed page number this content appears on
+    pub fn page_number(&self) -> Option<u32> {
         match self {
-            Self::Question(ref c) => true,
-            _ => false
+            Self::Question(ref question) => Some(question.page_number),
+            Self::Passage(ref passage) => Some(passage.page_number),
+            Self::Page(ref page) => Some(page.page_number),
+            Self::Figure(ref figure) => Some(figure.page_number),
+            Self::Table(ref table) => Some(table.page_number),
+            _ => None
         }
     }
 
-    pub fn is_section(&self) -> bool {
+    /// How the node is named when read out, e.g. "question 3b" or "the passage on page 12"
+    pub fn name(&self) -> String {
         match self {
-            Self::Section(ref c) => true,
-            _ => false
+            Self::Document => "the paper".to_string(),
+            Self::Section(ref section) if !section.section_name.is_empty() => section.section_name.clone(),
+            Self::Section(_) => "this section".to_string(),
+            Self::Question(ref question) => format!("question {}", question.label),
+            Self::Passage(ref passage) => format!("the passage on page {}", passage.page_number),
+            Self::Paragraph(ref paragraph) => format!("paragraph {}", paragraph.paragraph),
+            Self::Page(ref page) => format!("page {}", page.page_number),
+            Self::Figure(ref figure) => format!("the figure on page {}", figure.page_number),
+            Self::Table(ref table) => format!("the table on page {}", table.page_number),
+            Self::TableRow(ref row) => format!("row {}", row.row),
+            Self::TableCell(ref cell) => format!("row {} column {}", cell.row, cell.column),
+            Self::Instruction(_) => "the instructions".to_string()
         }
     }
 
@@ -40,13 +95,178 @@ pub struct SectionData {
     pub section_name: String
 }
 
+impl SectionData {
+    /// Describe the progress through this section
+    /// e.g. "Section B: 5 questions, 2 answered, 1 marked, 2 remaining"
+    pub fn describe(&self) -> String {
+        let mut parts = vec![
+            format!("{} {}", self.num_of_questions, plural(self.num_of_questions, "question")),
+            format!("{} answered", self.num_of_attempted),
+            format!("{} marked", self.num_of_marked)
+        ];
+
+        if self.num_of_skipped > 0 {
+            parts.push(format!("{} skipped", self.num_of_skipped));
+        }
+
+        parts.push(format!("{} remaining", self.num_of_remaining));
+
+        format!("{}: {}", self.section_name, parts.join(", "))
+    }
+}
+
 /// Question 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct QuestionData{
     pub question: String,
+    /// ordinal of the question, continuous or per section depending on the paper numbering
     pub question_number: u32,
+    /// the printed question label e.g. "3b"
+    pub label: String,
+    pub page_number: u32,
+    pub marked: bool,
+    /// maths in the question, the question text holds its spoken form
+    pub math: Vec<MathExpression>,
+    /// the options of a multiple choice question
+    pub choices: Option<Choices>
+}
+
+/// The options of a multiple choice question
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+pub struct Choices {
+    /// more than one option may be chosen
+    pub multiple: bool,
+    pub options: Vec<ChoiceOption>
+}
+
+/// A single option, e.g. "B" for "the mitochondria"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChoiceOption {
+    pub label: String,
+    pub text: String
+}
+
+impl Choices {
+    /// The option with a spoken label, as in "b" or "option B"
+    pub fn option(&self, label: &str) -> Option<&ChoiceOption> {
+        let label = Self::normalize(label);
+
+        self.options.iter()
+            .find(|option| Self::normalize(&option.label) == label)
+    }
+
+    /// The labels that can be chosen, e.g. "A, B, C or D"
+    pub fn labels(&self) -> String {
+        let labels: Vec<&str> = self.options.iter()
+            .map(|option| option.label.as_str())
+            .collect();
+
+        match labels.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            Some((last, _)) => last.to_string(),
+            None => String::new()
+        }
+    }
+
+    // labels compare without case, spaces or a leading "option"
+    fn normalize(label: &str) -> String {
+        let label = label.trim().to_lowercase();
+        let label = label.trim_start_matches("option");
+
+        label.chars().filter(|c| c.is_alphanumeric()).collect()
+    }
+}
+
+/// Markup language of a maths expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MathFormat {
+    MathML,
+    LaTeX
+}
+
+/// A maths expression with its original markup kept for visual and braille output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MathExpression {
+    pub format: MathFormat,
+    pub markup: String,
+    pub spoken: String
+}
+
+/// A reading passage within a section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PassageData {
+    pub passage: String,
+    pub page_number: u32
+}
+
+/// A paragraph of a passage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParagraphData {
+    /// position in the passage starting from 1
+    pub paragraph: u32,
+    pub text: String
+}
+
+/// A printed page, questions and passages link to the page they appear on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageData {
     pub page_number: u32,
-    pub marked: bool
+    /// labels of the questions printed on this page
+    pub questions: Vec<String>,
+    pub num_of_passages: u32
+}
+
+/// Kind of visual content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FigureKind {
+    Figure,
+    Image
+}
+
+/// A diagram or image the candidate must hear about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FigureData {
+    pub kind: FigureKind,
+    pub caption: Option<String>,
+    pub alt_text: Option<String>,
+    pub source: Option<String>,
+    pub page_number: u32
+}
+
+/// A table, its rows and cells are child nodes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableData {
+    pub caption: Option<String>,
+    pub headers: Vec<String>,
+    pub num_of_rows: u32,
+    pub num_of_columns: u32,
+    pub page_number: u32
+}
+
+/// A single table row, numbered from 1 excluding the header row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableRowData {
+    pub row: u32,
+    pub cells: Vec<TableCellData>
+}
+
+/// A single table cell together with its column header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableCellData {
+    pub row: u32,
+    pub column: u32,
+    pub header: Option<String>,
+    pub value: String
+}
+
+impl PageData {
+    pub fn new(page_number: u32) -> Self {
+        PageData {
+            page_number,
+            questions: vec![],
+            num_of_passages: 0
+        }
+    }
 }
 
 impl Default for QuestionData {
@@ -54,20 +274,242 @@ impl Default for QuestionData {
         QuestionData {
             question: String::new(),
             question_number: 0,
+            label: String::new(),
             page_number: 1,
-            marked: false
+            marked: false,
+            math: vec![],
+            choices: None
         }
     }
 }
 
 
+/// Paper wide information parsed from the meta_data block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+pub struct PaperMetadata {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub paper_code: Option<String>,
+    pub exam_board: Option<String>,
+    pub date: Option<String>,
+    pub duration: Option<String>,
+    pub duration_minutes: Option<u32>,
+    pub total_marks: Option<u32>,
+    pub instructions: Vec<String>,
+    pub numbering: Numbering,
+    /// the language answers are dictated in
+    pub locale: Locale
+}
+
+/// How question ordinals are counted across the paper
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Numbering {
+    /// questions are numbered 1..n across the whole paper
+    #[default]
+    Continuous,
+    /// numbering restarts at 1 in every section
+    PerSection
+}
+
+impl PaperMetadata {
+    /// Describe the paper, e.g. for "what paper is this"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(ref title) = self.title {
+            parts.push(title.clone());
+        }
+
+        if let Some(ref subject) = self.subject {
+            parts.push(subject.clone());
+        }
+
+        if let Some(ref code) = self.paper_code {
+            parts.push(format!("paper {}", code));
+        }
+
+        if let Some(ref board) = self.exam_board {
+            parts.push(format!("set by {}", board));
+        }
+
+        if let Some(ref date) = self.date {
+            parts.push(format!("sat on {}", date));
+        }
+
+        if parts.is_empty(){
+            return "This paper has no title information".to_string();
+        }
+
+        format!("This is {}", parts.join(", "))
+    }
+
+    /// Describe how long the exam lasts
+    pub fn describe_duration(&self) -> String {
+        match (self.duration_minutes, &self.duration) {
+            (Some(minutes), _) => {
+                let (hours, minutes) = (minutes / 60, minutes % 60);
+
+                match (hours, minutes) {
+                    (0, m) => format!("This exam lasts {} minutes", m),
+                    (h, 0) => format!("This exam lasts {} {}", h, plural(h, "hour")),
+                    (h, m) => format!("This exam lasts {} {} and {} minutes", h, plural(h, "hour"), m)
+                }
+            },
+            (None, Some(duration)) => format!("This exam lasts {}", duration),
+            _ => "The paper does not say how long the exam lasts".to_string()
+        }
+    }
+
+    /// Describe the total marks available
+    pub fn describe_total_marks(&self) -> String {
+        match self.total_marks {
+            Some(marks) => format!("This paper is out of {} marks", marks),
+            None => "The paper does not say how many marks are available".to_string()
+        }
+    }
+}
+
+pub(crate) fn plural(count: u32, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    }else{
+        format!("{}s", word)
+    }
+}
+
+/// Where the candidate is with a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Status {
+    Unseen,
+    Visited,
+    Answered,
+    MarkedForReview,
+    Skipped
+}
+
+/// Status of a single node, kept beside the node arena.
+/// Each field holds the time in seconds since the unix epoch it was last set
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub visited_at: Option<u64>,
+    pub answered_at: Option<u64>,
+    pub marked_at: Option<u64>,
+    pub skipped_at: Option<u64>,
+    /// labels of the options chosen on a multiple choice question, in the order they were chosen
+    #[serde(default)]
+    pub selected: Vec<String>
+}
+
+impl NodeStatus {
+    /// The status that best describes the node, marking for review wins over answering
+    pub fn status(&self) -> Status {
+        if self.marked_at.is_some(){
+            Status::MarkedForReview
+        }else if self.answered_at.is_some(){
+            Status::Answered
+        }else if self.skipped_at.is_some(){
+            Status::Skipped
+        }else if self.visited_at.is_some(){
+            Status::Visited
+        }else{
+            Status::Unseen
+        }
+    }
+
+    pub fn is_marked(&self) -> bool {
+        self.marked_at.is_some()
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.skipped_at.is_some()
+    }
+
+    pub fn is_answered(&self) -> bool {
+        self.answered_at.is_some()
+    }
+
+    pub fn is_visited(&self) -> bool {
+        self.visited_at.is_some()
+    }
+
+    /// The current time in seconds since the unix epoch
+    pub fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+}
+
 /// A note can be taken on any node
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Note{
     pub index: usize,
     pub note: String
 }
 
+/// Questions by printed label and sections by name, built once when a paper is loaded
+#[derive(Debug, Clone, Default)]
+pub struct LabelIndex {
+    // labels repeat when numbering restarts in each section
+    questions: HashMap<String, Vec<usize>>,
+    sections: HashMap<String, usize>,
+    // every section in document order, "section 2" is the second
+    ordinals: Vec<usize>
+}
+
+impl LabelIndex {
+    pub fn new(nodes: &[Node]) -> Self {
+        let mut index = LabelIndex::default();
+
+        for node in nodes {
+            match node.data {
+                NodeData::Question(ref question) => {
+                    index.questions.entry(Self::normalize(&question.label))
+                        .or_insert_with(Vec::new)
+                        .push(node.index);
+                },
+                NodeData::Section(ref section) => {
+                    if !section.section_name.is_empty() {
+                        index.sections.entry(Self::normalize(&section.section_name))
+                            .or_insert(node.index);
+                    }
+
+                    index.ordinals.push(node.index);
+                },
+                _ => ()
+            }
+        }
+
+        index
+    }
+
+    /// Reduce "Question 3(b)" to "3b" and "Section B" to "b"
+    pub fn normalize(label: &str) -> String {
+        let label = label.trim().to_lowercase();
+        let label = label.trim_start_matches("question")
+            .trim_start_matches("section");
+
+        label.chars().filter(|c| c.is_alphanumeric()).collect()
+    }
+
+    /// The questions printed with a label in document order
+    pub fn questions(&self, label: &str) -> &[usize] {
+        self.questions.get(&Self::normalize(label))
+            .map(|questions| questions.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The section with a name
+    pub fn section(&self, name: &str) -> Option<usize> {
+        self.sections.get(&Self::normalize(name)).copied()
+    }
+
+    /// The section at a position in the paper, counting from 1
+    pub fn nth_section(&self, ordinal: usize) -> Option<usize> {
+        ordinal.checked_sub(1).and_then(|position| self.ordinals.get(position)).copied()
+    }
+}
+
 /// A single document node
 #[derive(Debug, Clone)]
 pub struct Node {
@@ -77,10 +519,13 @@ pub struct Node {
     pub prev: Option<usize>,
     pub next: Option<usize>,
     pub first_child: Option<usize>,
-    pub last_child: Option<usize>
+    pub last_child: Option<usize>,
+    /// the page node this node appears on
+    pub page: Option<usize>
 }
 
 /// A type to index the nodes
+#[derive(Clone, Copy)]
 pub struct NodeIndex<'a> {
     question_paper: &'a QuestionPaper,
     index: usize
@@ -95,7 +540,7 @@ impl<'a> NodeIndex<'a> {
         }
     }
 
-    pub fn raw(&self) -> &Node{
+    pub fn raw(&self) -> &'a Node{
         &self.question_paper.nodes[self.index()]
     }
 
@@ -103,9 +548,161 @@ impl<'a> NodeIndex<'a> {
         &self.raw().data
     }
 
+    pub fn status(&self) -> &NodeStatus {
+        &self.question_paper.statuses[self.index()]
+    }
+
     pub fn index(&self) -> usize {
         self.index
     }
+
+    // index another node of the same paper
+    fn at(&self, index: Option<usize>) -> Option<NodeIndex<'a>> {
+        index.and_then(|index| NodeIndex::new(self.question_paper, index))
+    }
+
+    pub fn parent(&self) -> Option<NodeIndex<'a>> {
+        self.at(self.raw().parent)
+    }
+
+    pub fn first_child(&self) -> Option<NodeIndex<'a>> {
+        self.at(self.raw().first_child)
+    }
+
+    pub fn last_child(&self) -> Option<NodeIndex<'a>> {
+        self.at(self.raw().last_child)
+    }
+
+    pub fn next_sibling(&self) -> Option<NodeIndex<'a>> {
+        self.at(self.raw().next)
+    }
+
+    pub fn prev_sibling(&self) -> Option<NodeIndex<'a>> {
+        self.at(self.raw().prev)
+    }
+
+    /// The children of this node in document order
+    pub fn children(&self) -> Siblings<'a> {
+        Siblings {
+            next: self.first_child(),
+            forward: true
+        }
+    }
+
+    /// The parent, grandparent and so on up to the document
+    pub fn ancestors(&self) -> Ancestors<'a> {
+        Ancestors {
+            next: self.parent()
+        }
+    }
+
+    /// The siblings after this node
+    pub fn following_siblings(&self) -> Siblings<'a> {
+        Siblings {
+            next: self.next_sibling(),
+            forward: true
+        }
+    }
+
+    /// The siblings before this node, nearest first
+    pub fn preceding_siblings(&self) -> Siblings<'a> {
+        Siblings {
+            next: self.prev_sibling(),
+            forward: false
+        }
+    }
+
+    /// Every node below this one in document order
+    pub fn descendants(&self) -> Descendants<'a> {
+        Descendants {
+            root: self.index(),
+            next: self.first_child()
+        }
+    }
+
+    /// The nearest ancestor, or this node itself, matching a predicate
+    pub fn closest<P: Predicate>(&self, predicate: &P) -> Option<NodeIndex<'a>> {
+        if predicate.matches(self) {
+            return Some(*self);
+        }
+
+        self.ancestors().find(|node| predicate.matches(node))
+    }
+}
+
+/// Iterates over siblings in either direction
+pub struct Siblings<'a> {
+    next: Option<NodeIndex<'a>>,
+    forward: bool
+}
+
+impl<'a> Iterator for Siblings<'a> {
+    type Item = NodeIndex<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+
+        self.next = if self.forward {
+            node.next_sibling()
+        }else{
+            node.prev_sibling()
+        };
+
+        Some(node)
+    }
+}
+
+/// Iterates up the tree towards the document
+pub struct Ancestors<'a> {
+    next: Option<NodeIndex<'a>>
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = NodeIndex<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+
+        self.next = node.parent();
+
+        Some(node)
+    }
+}
+
+/// Iterates over a subtree in document order, excluding its root
+pub struct Descendants<'a> {
+    root: usize,
+    next: Option<NodeIndex<'a>>
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeIndex<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+        let root = self.root;
+
+        // go down first, then along, then back up until a sibling is found
+        self.next = node.first_child().or_else(|| {
+            let mut current = Some(node);
+
+            while let Some(n) = current {
+                if n.index() == root {
+                    return None;
+                }
+
+                if let Some(sibling) = n.next_sibling() {
+                    return Some(sibling);
+                }
+
+                current = n.parent();
+            }
+
+            None
+        });
+
+        Some(node)
+    }
 }
 
 /// A trait for all types that can be used to build different fragments
@@ -114,7 +711,52 @@ pub trait Builder {
 
     fn process_tag(&mut self, tag: Tag);
 
-    fn end(&mut self) -> Self::Item;
+    fn end(&mut self) -> Result<Self::Item, BuildErrors>;
+}
+
+/// A problem found while building a question paper
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildError {
+    /// line in the uploaded document, if known
+    pub line: Option<u64>,
+    pub message: Cow<'static, str>
+}
+
+impl BuildError {
+    pub fn new(message: Cow<'static, str>) -> Self {
+        BuildError {
+            line: None,
+            message
+        }
+    }
+
+    pub fn at(tag: &Tag, message: Cow<'static, str>) -> Self {
+        BuildError {
+            line: Some(tag.line),
+            message
+        }
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message)
+        }
+    }
+}
+
+/// All the problems found while building a question paper
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildErrors(pub Vec<BuildError>);
+
+impl fmt::Display for BuildErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<String> = self.0.iter().map(|error| error.to_string()).collect();
+
+        write!(f, "{}", errors.join("; "))
+    }
 }
 
 /// A trait for all predicates to be applied on a node
@@ -124,6 +766,21 @@ pub trait Predicate {
     fn and<T: Predicate>(self, other: T) -> And<Self, T> where Self:Sized {
         And(self, other)
     }
+
+    fn or<T: Predicate>(self, other: T) -> Or<Self, T> where Self:Sized {
+        Or(self, other)
+    }
+
+    fn not(self) -> Not<Self> where Self:Sized {
+        Not(self)
+    }
+}
+
+// predicates chosen at runtime, as from the filters of a read intent
+impl Predicate for Box<dyn Predicate> {
+    fn matches(&self, other: &NodeIndex) -> bool {
+        (**self).matches(other)
+    }
 }
 
 // and two predicates
@@ -136,6 +793,26 @@ impl<A: Predicate, B: Predicate> Predicate for And<A, B> {
     }
 }
 
+// or two predicates
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: Predicate, B: Predicate> Predicate for Or<A, B> {
+    fn matches(&self, other: &NodeIndex) -> bool {
+        self.0.matches(other) || self.1.matches(other)
+    }
+}
+
+// negate a predicate
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Not<A>(pub A);
+
+impl<A: Predicate> Predicate for Not<A> {
+    fn matches(&self, other: &NodeIndex) -> bool {
+        !self.0.matches(other)
+    }
+}
+
 
 // predicates to search the document
 pub mod predicates {
@@ -150,6 +827,103 @@ pub mod predicates {
         }
     }
 
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct PagePredicate;
+
+    impl Predicate for PagePredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.data().is_page()
+        }
+    }
+
+    /// Matches the page node with a given printed number
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct PageNumberPredicate(pub u32);
+
+    impl Predicate for PageNumberPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            match other.data() {
+                NodeData::Page(ref page) => page.page_number == self.0,
+                _ => false
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct PassagePredicate;
+
+    impl Predicate for PassagePredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.data().is_passage()
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct ParagraphPredicate;
+
+    impl Predicate for ParagraphPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.data().is_paragraph()
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct TableRowPredicate;
+
+    impl Predicate for TableRowPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            matches!(other.data(), NodeData::TableRow(_))
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct TableCellPredicate;
+
+    impl Predicate for TableCellPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            matches!(other.data(), NodeData::TableCell(_))
+        }
+    }
+
+    /// Matches nodes that come after a node in document order
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct AfterPredicate(pub usize);
+
+    impl Predicate for AfterPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.index() > self.0
+        }
+    }
+
+    /// Matches nodes below a scope, or printed on it when the scope is a page
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct WithinPredicate(pub usize);
+
+    impl Predicate for WithinPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.raw().page == Some(self.0)
+                || other.ancestors().any(|ancestor| ancestor.index() == self.0)
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct FigurePredicate;
+
+    impl Predicate for FigurePredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.data().is_figure()
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct TablePredicate;
+
+    impl Predicate for TablePredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.data().is_table()
+        }
+    }
+
     #[derive(Copy, Clone, Debug, PartialEq)]
     pub struct SectionPredicate;
 
@@ -158,4 +932,135 @@ pub mod predicates {
             other.data().is_section()
         }
     }
-}
\ No newline at end of file
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct MarkedPredicate;
+
+    impl Predicate for MarkedPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.status().is_marked()
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct SkippedPredicate;
+
+    impl Predicate for SkippedPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.status().is_skipped()
+        }
+    }
+
+    /// Matches questions that have not been answered yet
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct UnansweredPredicate;
+
+    impl Predicate for UnansweredPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.data().is_question() && !other.status().is_answered()
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct VisitedPredicate;
+
+    impl Predicate for VisitedPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.status().is_visited()
+        }
+    }
+
+    /// Matches content printed on a given page
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct OnPagePredicate(pub u32);
+
+    impl Predicate for OnPagePredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            !other.data().is_page() && other.data().page_number() == Some(self.0)
+        }
+    }
+
+    /// Matches nodes inside the section at a node index
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct InSectionPredicate(pub usize);
+
+    impl Predicate for InSectionPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.ancestors().any(|ancestor| ancestor.index() == self.0)
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct HasNotePredicate;
+
+    impl Predicate for HasNotePredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            other.question_paper.notes.iter().any(|note| note.index == other.index())
+        }
+    }
+
+    /// Matches nodes whose text contains a phrase, ignoring case
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct TextContainsPredicate(pub String);
+
+    impl Predicate for TextContainsPredicate {
+        fn matches(&self, other: &NodeIndex) -> bool {
+            let text = match other.data() {
+                NodeData::Question(ref question) => &question.question,
+                NodeData::Passage(ref passage) => &passage.passage,
+                NodeData::Paragraph(ref paragraph) => &paragraph.text,
+                NodeData::TableCell(ref cell) => &cell.value,
+                NodeData::Figure(FigureData { caption: Some(ref caption), .. }) => caption,
+                _ => return false
+            };
+
+            text.to_lowercase().contains(&self.0.to_lowercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_normalized(){
+        assert_eq!(LabelIndex::normalize("Question 3(b)"), "3b");
+        assert_eq!(LabelIndex::normalize("Section B"), "b");
+        assert_eq!(LabelIndex::normalize(" 12 "), "12");
+    }
+
+    #[test]
+    fn options_by_spoken_label(){
+        let choices = Choices {
+            multiple: false,
+            options: ["A", "B", "C"].iter()
+                .map(|label| ChoiceOption { label: label.to_string(), text: String::new() })
+                .collect()
+        };
+
+        assert_eq!(choices.option("option b").map(|option| option.label.as_str()), Some("B"));
+        assert_eq!(choices.option("C").map(|option| option.label.as_str()), Some("C"));
+        assert!(choices.option("D").is_none());
+        assert_eq!(choices.labels(), "A, B or C");
+    }
+
+    #[test]
+    fn statuses(){
+        let mut status = NodeStatus::default();
+
+        assert_eq!(status.status(), Status::Unseen);
+
+        status.visited_at = Some(1);
+        assert_eq!(status.status(), Status::Visited);
+
+        status.skipped_at = Some(2);
+        assert_eq!(status.status(), Status::Skipped);
+
+        status.answered_at = Some(3);
+        assert_eq!(status.status(), Status::Answered);
+
+        status.marked_at = Some(4);
+        assert_eq!(status.status(), Status::MarkedForReview);
+    }
+}
@@ -0,0 +1,334 @@
+use super::MathNode;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A small recursive descent parser for the LaTeX found in question text
+pub struct LatexParser<'a> {
+    input: Peekable<Chars<'a>>
+}
+
+impl<'a> LatexParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        LatexParser {
+            input: input.chars().peekable()
+        }
+    }
+
+    /// Parse a whole expression
+    pub fn parse(input: &str) -> MathNode {
+        let mut parser = LatexParser::new(input);
+
+        MathNode::row(parser.parse_row(None))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.input.peek() {
+            if c.is_whitespace(){
+                self.input.next();
+            }else{
+                break;
+            }
+        }
+    }
+
+    // parse nodes until the closing character or the end of input
+    fn parse_row(&mut self, close: Option<char>) -> Vec<MathNode> {
+        let mut nodes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            match self.input.peek() {
+                None => break,
+                Some(&c) if Some(c) == close => {
+                    self.input.next();
+                    break;
+                },
+                _ => ()
+            }
+
+            match self.parse_atom() {
+                Some(atom) => {
+                    let atom = self.parse_scripts(atom);
+                    nodes.push(atom);
+                },
+                None => break
+            }
+        }
+
+        nodes
+    }
+
+    // superscripts and subscripts following an atom
+    fn parse_scripts(&mut self, mut atom: MathNode) -> MathNode {
+        loop {
+            self.skip_whitespace();
+
+            match self.input.peek() {
+                Some('^') => {
+                    self.input.next();
+                    let exponent = self.parse_argument();
+                    atom = MathNode::Power(Box::new(atom), Box::new(exponent));
+                },
+                Some('_') => {
+                    self.input.next();
+                    let subscript = self.parse_argument();
+                    atom = MathNode::Subscript(Box::new(atom), Box::new(subscript));
+                },
+                _ => return atom
+            }
+        }
+    }
+
+    // a braced group or a single character argument
+    fn parse_argument(&mut self) -> MathNode {
+        self.skip_whitespace();
+
+        match self.input.peek() {
+            Some('{') => {
+                self.input.next();
+                MathNode::row(self.parse_row(Some('}')))
+            },
+            Some(c) if c.is_ascii_digit() => {
+                // only a single digit is taken without braces
+                MathNode::Number(self.input.next().unwrap().to_string())
+            },
+            _ => self.parse_atom().unwrap_or_else(|| MathNode::Row(vec![]))
+        }
+    }
+
+    fn parse_atom(&mut self) -> Option<MathNode> {
+        let c = *self.input.peek()?;
+
+        if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+
+            while let Some(&c) = self.input.peek() {
+                if c.is_ascii_digit() || c == '.' {
+                    number.push(c);
+                    self.input.next();
+                }else{
+                    break;
+                }
+            }
+
+            return Some(MathNode::Number(number));
+        }
+
+        self.input.next();
+
+        let atom = match c {
+            '{' => MathNode::row(self.parse_row(Some('}'))),
+            '(' => MathNode::Fenced(Box::new(MathNode::row(self.parse_row(Some(')'))))),
+            '[' => MathNode::Fenced(Box::new(MathNode::row(self.parse_row(Some(']'))))),
+            '\\' => self.parse_command(),
+            c if c.is_alphabetic() => MathNode::Identifier(c.to_string()),
+            c => MathNode::Operator(c.to_string())
+        };
+
+        Some(atom)
+    }
+
+    fn parse_command(&mut self) -> MathNode {
+        let mut name = String::new();
+
+        while let Some(&c) = self.input.peek() {
+            if c.is_ascii_alphabetic(){
+                name.push(c);
+                self.input.next();
+            }else{
+                break;
+            }
+        }
+
+        // escaped symbols such as \{ or \%
+        if name.is_empty(){
+            return match self.input.next() {
+                Some(c) => MathNode::Operator(c.to_string()),
+                None => MathNode::Row(vec![])
+            };
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+
+                MathNode::Fraction(Box::new(numerator), Box::new(denominator))
+            },
+            "sqrt" => {
+                self.skip_whitespace();
+
+                let index = if self.input.peek() == Some(&'[') {
+                    self.input.next();
+                    Some(Box::new(MathNode::row(self.parse_row(Some(']')))))
+                }else{
+                    None
+                };
+
+                MathNode::Root(Box::new(self.parse_argument()), index)
+            },
+            // sizing commands around brackets are not spoken
+            "left" | "right" => {
+                self.skip_whitespace();
+
+                match self.input.peek() {
+                    Some('(') | Some('[') => self.parse_atom().unwrap_or_else(|| MathNode::Row(vec![])),
+                    _ => {
+                        self.input.next();
+                        MathNode::Row(vec![])
+                    }
+                }
+            },
+            "text" | "mathrm" | "mbox" => {
+                match self.parse_argument() {
+                    MathNode::Row(nodes) => MathNode::Text(nodes.iter().map(|node| node.speak()).collect::<Vec<_>>().join("")),
+                    node => MathNode::Text(node.speak())
+                }
+            },
+            "times" | "cdot" | "div" | "pm" | "leq" | "le" | "geq" | "ge" | "neq" | "ne" | "approx" | "circ" => {
+                MathNode::Operator(name)
+            },
+            _ => MathNode::Identifier(name)
+        }
+    }
+}
+
+/// Find LaTeX delimited by `\( \)`, `\[ \]` or `$ $` in a piece of text.
+/// A `$` without a closing `$` hugging the maths is a currency sign, as in "it costs $5 and $10".
+/// Returns the text with each expression spoken and the expressions found
+pub fn replace_in_text(text: &str) -> (String, Vec<(String, MathNode)>) {
+    let mut spoken = String::new();
+    let mut found = Vec::new();
+    let mut rest = text;
+
+    loop {
+        let start = [("\\(", "\\)"), ("\\[", "\\]"), ("$", "$")].iter()
+            .filter_map(|&(open, close)| find_open(rest, open).map(|index| (index, open, close)))
+            .min_by_key(|&(index, _, _)| index);
+
+        let (index, open, close) = match start {
+            Some(start) => start,
+            None => break
+        };
+
+        let after = &rest[index + open.len()..];
+
+        let end = match after.find(close) {
+            Some(end) => end,
+            None => break
+        };
+
+        let markup = &after[..end];
+        let node = LatexParser::parse(markup);
+
+        spoken.push_str(&rest[..index]);
+        spoken.push_str(&node.speak());
+        found.push((markup.to_string(), node));
+
+        rest = &after[end + close.len()..];
+    }
+
+    spoken.push_str(rest);
+
+    (spoken, found)
+}
+
+// where an expression opens, skipping dollar signs used for money
+fn find_open(text: &str, open: &str) -> Option<usize> {
+    if open != "$" {
+        return text.find(open);
+    }
+
+    let signs: Vec<usize> = text.match_indices(open).map(|(index, _)| index).collect();
+
+    signs.windows(2)
+        .find(|pair| is_math(text, pair[0], pair[1]))
+        .map(|pair| pair[0])
+}
+
+// maths hugs both of its dollar signs, as in "$2x + 3 = 7$", while money has no closing sign
+// next to it, as in "$5 and $10", or only holds an amount, as in "$10.50$"
+fn is_math(text: &str, open: usize, close: usize) -> bool {
+    let inner = &text[open + 1..close];
+    let hugged = inner.starts_with(|c: char| !c.is_whitespace()) && inner.ends_with(|c: char| !c.is_whitespace());
+    let before_digit = text[close + 1..].starts_with(|c: char| c.is_ascii_digit());
+
+    hugged && !before_digit && !is_amount(inner)
+}
+
+// a plain amount of money such as "5" or "10.50"
+fn is_amount(text: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+
+    match text.split_once('.') {
+        Some((whole, cents)) => digits(whole) && digits(cents),
+        None => digits(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn currency_is_text(){
+        let (spoken, found) = replace_in_text("It costs $5 and $10");
+
+        assert_eq!(spoken, "It costs $5 and $10");
+        assert!(found.is_empty());
+
+        let (spoken, found) = replace_in_text("Pay $ 20 now, or $ 30 later");
+
+        assert_eq!(spoken, "Pay $ 20 now, or $ 30 later");
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn currency_beside_maths(){
+        let (spoken, found) = replace_in_text("A pen costs $3, find $x$");
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "x");
+        assert!(spoken.starts_with("A pen costs $3, find "));
+    }
+
+    #[test]
+    fn delimiters(){
+        for text in &["Solve $x^2$ now", "Solve \\(x^2\\) now", "Solve \\[x^2\\] now"] {
+            let (spoken, found) = replace_in_text(text);
+
+            assert_eq!(found.len(), 1, "{}", text);
+            assert_eq!(found[0].0, "x^2");
+            assert!(!spoken.contains('$') && !spoken.contains('\\'), "{}", spoken);
+        }
+    }
+
+    #[test]
+    fn maths_starting_with_a_number(){
+        for (text, markup) in &[("Solve $2x + 3 = 7$ for x", "2x + 3 = 7"), ("Find $3^2$", "3^2"), ("It costs $5, find $2y$", "2y")] {
+            let (_, found) = replace_in_text(text);
+
+            assert_eq!(found.len(), 1, "{}", text);
+            assert_eq!(found[0].0, *markup);
+        }
+    }
+
+    #[test]
+    fn amounts_between_dollar_signs_are_text(){
+        for text in &["Pay $5$ each", "Pay $10.50$ each", "Pay $5,$10 or $ 20 $"] {
+            let (spoken, found) = replace_in_text(text);
+
+            assert_eq!(spoken, *text);
+            assert!(found.is_empty(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn unclosed_is_text(){
+        let (spoken, found) = replace_in_text("Solve $x now");
+
+        assert_eq!(spoken, "Solve $x now");
+        assert!(found.is_empty());
+    }
+}
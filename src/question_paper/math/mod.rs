@@ -0,0 +1,344 @@
+pub mod latex;
+pub mod spoken;
+
+/// Greek letters and infinity as (spoken name, LaTeX command, symbol)
+const GREEK: &[(&str, &str, &str)] = &[
+    ("alpha", "alpha", "α"),
+    ("beta", "beta", "β"),
+    ("gamma", "gamma", "γ"),
+    ("delta", "delta", "δ"),
+    ("theta", "theta", "θ"),
+    ("lambda", "lambda", "λ"),
+    ("mu", "mu", "μ"),
+    ("pi", "pi", "π"),
+    ("sigma", "sigma", "σ"),
+    ("phi", "phi", "φ"),
+    ("omega", "omega", "ω"),
+    ("infinity", "infty", "∞")
+];
+
+/// A parsed mathematical expression, spoken using ClearSpeak style rules
+/// e.g. `x^2+3x-4=0` is spoken as "x squared plus 3 x minus 4 equals 0"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MathNode {
+    Number(String),
+    Identifier(String),
+    Operator(String),
+    Text(String),
+    Row(Vec<MathNode>),
+    Fraction(Box<MathNode>, Box<MathNode>),
+    Power(Box<MathNode>, Box<MathNode>),
+    Subscript(Box<MathNode>, Box<MathNode>),
+    /// radicand and an optional index, a square root has no index
+    Root(Box<MathNode>, Option<Box<MathNode>>),
+    /// an expression in brackets
+    Fenced(Box<MathNode>)
+}
+
+impl MathNode {
+    /// Wrap several nodes into a row, a single node is returned as is
+    pub fn row(mut nodes: Vec<MathNode>) -> MathNode {
+        if nodes.len() == 1 {
+            nodes.remove(0)
+        }else{
+            MathNode::Row(nodes)
+        }
+    }
+
+    // a single token that can be spoken without marking where it ends
+    fn is_simple(&self) -> bool {
+        match self {
+            MathNode::Number(_) | MathNode::Identifier(_) | MathNode::Text(_) => true,
+            MathNode::Row(ref nodes) => nodes.len() == 1 && nodes[0].is_simple(),
+            _ => false
+        }
+    }
+
+    fn as_number(&self) -> Option<&str> {
+        match self {
+            MathNode::Number(ref number) => Some(number),
+            MathNode::Row(ref nodes) if nodes.len() == 1 => nodes[0].as_number(),
+            _ => None
+        }
+    }
+
+    /// Speak this expression
+    pub fn speak(&self) -> String {
+        match self {
+            MathNode::Number(ref number) => number.clone(),
+            MathNode::Identifier(ref identifier) => speak_identifier(identifier),
+            MathNode::Operator(ref operator) => speak_operator(operator),
+            MathNode::Text(ref text) => text.trim().to_string(),
+            MathNode::Row(ref nodes) => {
+                nodes.iter()
+                    .map(|node| node.speak())
+                    .filter(|spoken| !spoken.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            },
+            MathNode::Fraction(ref numerator, ref denominator) => {
+                if numerator.is_simple() && denominator.is_simple() {
+                    format!("{} over {}", numerator.speak(), denominator.speak())
+                }else{
+                    format!(
+                        "the fraction with numerator {} and denominator {}",
+                        numerator.speak(),
+                        denominator.speak()
+                    )
+                }
+            },
+            MathNode::Power(ref base, ref exponent) => {
+                let base = base.speak();
+
+                match exponent.as_number() {
+                    Some("2") => format!("{} squared", base),
+                    Some("3") => format!("{} cubed", base),
+                    Some(n) => format!("{} to the power of {}", base, n),
+                    None if exponent.is_simple() => format!("{} to the power of {}", base, exponent.speak()),
+                    None => format!("{} raised to the power {}, end exponent", base, exponent.speak())
+                }
+            },
+            MathNode::Subscript(ref base, ref subscript) => {
+                format!("{} sub {}", base.speak(), subscript.speak())
+            },
+            MathNode::Root(ref radicand, ref index) => {
+                let root = match index.as_ref().and_then(|index| index.as_number()) {
+                    None if index.is_none() => "the square root of".to_string(),
+                    Some("2") => "the square root of".to_string(),
+                    Some("3") => "the cube root of".to_string(),
+                    Some(n) => format!("the {} root of", ordinal(n)),
+                    None => format!("the root with index {} of", index.as_ref().unwrap().speak())
+                };
+
+                if radicand.is_simple() {
+                    format!("{} {}", root, radicand.speak())
+                }else{
+                    format!("{} {}, end root", root, radicand.speak())
+                }
+            },
+            MathNode::Fenced(ref inner) => {
+                if inner.is_simple() {
+                    inner.speak()
+                }else{
+                    format!("open bracket {} close bracket", inner.speak())
+                }
+            }
+        }
+    }
+}
+
+impl MathNode {
+    /// Write this expression as LaTeX
+    pub fn to_latex(&self) -> String {
+        match self {
+            MathNode::Number(ref number) => number.clone(),
+            MathNode::Identifier(ref identifier) => latex_identifier(identifier),
+            MathNode::Operator(ref operator) => latex_operator(operator),
+            MathNode::Text(ref text) => format!("\\text{{{}}}", text),
+            MathNode::Row(ref nodes) => {
+                nodes.iter()
+                    .map(|node| node.to_latex())
+                    .filter(|latex| !latex.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            },
+            MathNode::Fraction(ref numerator, ref denominator) => {
+                format!("\\frac{{{}}}{{{}}}", numerator.to_latex(), denominator.to_latex())
+            },
+            MathNode::Power(ref base, ref exponent) => format!("{{{}}}^{{{}}}", base.to_latex(), exponent.to_latex()),
+            MathNode::Subscript(ref base, ref subscript) => format!("{{{}}}_{{{}}}", base.to_latex(), subscript.to_latex()),
+            MathNode::Root(ref radicand, None) => format!("\\sqrt{{{}}}", radicand.to_latex()),
+            MathNode::Root(ref radicand, Some(ref index)) => {
+                format!("\\sqrt[{}]{{{}}}", index.to_latex(), radicand.to_latex())
+            },
+            MathNode::Fenced(ref inner) => format!("\\left( {} \\right)", inner.to_latex())
+        }
+    }
+
+    /// Write this expression as presentation MathML
+    pub fn to_mathml(&self) -> String {
+        format!("<math>{}</math>", self.mathml_element())
+    }
+
+    fn mathml_element(&self) -> String {
+        match self {
+            MathNode::Number(ref number) => format!("<mn>{}</mn>", escape(number)),
+            MathNode::Identifier(ref identifier) => format!("<mi>{}</mi>", escape(mathml_identifier(identifier))),
+            MathNode::Operator(ref operator) => format!("<mo>{}</mo>", escape(mathml_operator(operator))),
+            MathNode::Text(ref text) => format!("<mtext>{}</mtext>", escape(text)),
+            MathNode::Row(ref nodes) => {
+                let children: String = nodes.iter().map(|node| node.mathml_element()).collect();
+
+                format!("<mrow>{}</mrow>", children)
+            },
+            MathNode::Fraction(ref numerator, ref denominator) => {
+                format!("<mfrac>{}{}</mfrac>", numerator.mathml_element(), denominator.mathml_element())
+            },
+            MathNode::Power(ref base, ref exponent) => {
+                format!("<msup>{}{}</msup>", base.mathml_element(), exponent.mathml_element())
+            },
+            MathNode::Subscript(ref base, ref subscript) => {
+                format!("<msub>{}{}</msub>", base.mathml_element(), subscript.mathml_element())
+            },
+            MathNode::Root(ref radicand, None) => format!("<msqrt>{}</msqrt>", radicand.mathml_element()),
+            MathNode::Root(ref radicand, Some(ref index)) => {
+                format!("<mroot>{}{}</mroot>", radicand.mathml_element(), index.mathml_element())
+            },
+            MathNode::Fenced(ref inner) => format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", inner.mathml_element())
+        }
+    }
+}
+
+// an identifier is either the symbol or the LaTeX command it was written with
+fn greek(identifier: &str) -> Option<&'static (&'static str, &'static str, &'static str)> {
+    GREEK.iter().find(|&&(_, command, symbol)| identifier == command || identifier == symbol)
+}
+
+fn latex_identifier(identifier: &str) -> String {
+    match greek(identifier) {
+        Some(&(_, command, _)) => format!("\\{}", command),
+        None => identifier.to_string()
+    }
+}
+
+fn latex_operator(operator: &str) -> String {
+    let command = match operator.trim() {
+        "×" | "*" | "times" => "\\times",
+        "·" | "⋅" | "cdot" => "\\cdot",
+        "÷" | "div" => "\\div",
+        "±" | "pm" => "\\pm",
+        "≤" | "leq" | "le" => "\\leq",
+        "≥" | "geq" | "ge" => "\\geq",
+        "≠" | "neq" | "ne" => "\\neq",
+        "≈" | "approx" => "\\approx",
+        "−" => "-",
+        "%" => "\\%",
+        "°" | "circ" => "^{\\circ}",
+        "\u{2062}" | "\u{2061}" => "",
+        other => return other.to_string()
+    };
+
+    command.to_string()
+}
+
+// LaTeX command names read from markup are written as the symbols they stand for
+fn mathml_identifier(identifier: &str) -> &str {
+    match greek(identifier) {
+        Some(&(_, _, symbol)) => symbol,
+        None => identifier
+    }
+}
+
+fn mathml_operator(operator: &str) -> &str {
+    match operator.trim() {
+        "*" | "times" => "×",
+        "cdot" => "⋅",
+        "div" => "÷",
+        "pm" => "±",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "circ" => "°",
+        _ => operator
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn speak_identifier(identifier: &str) -> String {
+    match greek(identifier) {
+        Some(&(spoken, _, _)) => spoken.to_string(),
+        None => identifier.to_string()
+    }
+}
+
+fn speak_operator(operator: &str) -> String {
+    let spoken = match operator.trim() {
+        "+" => "plus",
+        "-" | "−" => "minus",
+        "=" => "equals",
+        "×" | "*" | "·" | "⋅" | "times" | "cdot" => "times",
+        "÷" | "/" | "div" => "divided by",
+        "±" | "pm" => "plus or minus",
+        "<" | "&lt;" => "is less than",
+        ">" | "&gt;" => "is greater than",
+        "≤" | "leq" | "le" => "is less than or equal to",
+        "≥" | "geq" | "ge" => "is greater than or equal to",
+        "≠" | "neq" | "ne" => "is not equal to",
+        "≈" | "approx" => "is approximately equal to",
+        "," => "comma",
+        "%" => "percent",
+        "°" | "circ" => "degrees",
+        // invisible times and function application are not spoken
+        "\u{2062}" | "\u{2061}" | "" => "",
+        other => return other.to_string()
+    };
+
+    spoken.to_string()
+}
+
+fn ordinal(number: &str) -> String {
+    let suffix = match number.parse::<u32>() {
+        Ok(n) if n % 100 >= 11 && n % 100 <= 13 => "th",
+        Ok(n) if n % 10 == 1 => "st",
+        Ok(n) if n % 10 == 2 => "nd",
+        Ok(n) if n % 10 == 3 => "rd",
+        _ => "th"
+    };
+
+    format!("{}{}", number, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use latex::LatexParser;
+
+    #[test]
+    fn speaks_latex(){
+        assert_eq!(LatexParser::parse("\\frac{1}{2}").speak(), "1 over 2");
+        assert_eq!(LatexParser::parse("x^2 + 1").speak(), "x squared plus 1");
+        assert_eq!(LatexParser::parse("\\sqrt[3]{8}").speak(), "the cube root of 8");
+        assert_eq!(LatexParser::parse("x \\leq 5").speak(), "x is less than or equal to 5");
+    }
+
+    #[test]
+    fn writes_latex(){
+        assert_eq!(LatexParser::parse("a_1 \\times b").to_latex(), "{a}_{1} \\times b");
+        assert_eq!(LatexParser::parse("(x+1)").to_latex(), "\\left( x + 1 \\right)");
+        assert_eq!(MathNode::Identifier("π".to_string()).to_latex(), "\\pi");
+    }
+
+    #[test]
+    fn writes_mathml(){
+        assert_eq!(
+            LatexParser::parse("\\sqrt[3]{8}").to_mathml(),
+            "<math><mroot><mn>8</mn><mn>3</mn></mroot></math>"
+        );
+        assert_eq!(
+            LatexParser::parse("a_1 \\times b").to_mathml(),
+            "<math><mrow><msub><mi>a</mi><mn>1</mn></msub><mo>×</mo><mi>b</mi></mrow></math>"
+        );
+    }
+
+    #[test]
+    fn mathml_uses_symbols_and_escapes(){
+        assert_eq!(
+            LatexParser::parse("\\alpha < \\pi").to_mathml(),
+            "<math><mrow><mi>α</mi><mo>&lt;</mo><mi>π</mi></mrow></math>"
+        );
+        assert_eq!(LatexParser::parse("x \\leq 5").to_mathml(), "<math><mrow><mi>x</mi><mo>≤</mo><mn>5</mn></mrow></math>");
+    }
+
+    #[test]
+    fn greek_letters_by_symbol_or_command(){
+        assert_eq!(MathNode::Identifier("θ".to_string()).to_latex(), "\\theta");
+        assert_eq!(MathNode::Identifier("theta".to_string()).to_mathml(), "<math><mi>θ</mi></math>");
+        assert_eq!(MathNode::Identifier("∞".to_string()).speak(), "infinity");
+        assert_eq!(LatexParser::parse("\\infty").to_latex(), "\\infty");
+        assert_eq!(MathNode::Identifier("omicron".to_string()).to_latex(), "omicron");
+    }
+}
@@ -0,0 +1,527 @@
+use super::{MathNode, GREEK};
+use std::borrow::Cow::{self, Borrowed};
+
+// relations, longer phrases first
+const RELATIONS: &[(&str, &str)] = &[
+    ("is less than or equal to", "≤"),
+    ("is greater than or equal to", "≥"),
+    ("less than or equal to", "≤"),
+    ("greater than or equal to", "≥"),
+    ("is not equal to", "≠"),
+    ("is approximately equal to", "≈"),
+    ("is equal to", "="),
+    ("equal to", "="),
+    ("equals", "="),
+    ("equal", "="),
+    ("=", "="),
+    ("is less than", "<"),
+    ("less than", "<"),
+    ("is greater than", ">"),
+    ("greater than", ">")
+];
+
+const UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen"
+];
+
+const TENS: &[&str] = &["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+const TOO_LARGE: Cow<'static, str> = Borrowed("A number in your maths is too large");
+
+const ORDINALS: &[&str] = &["", "", "", "", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth"];
+
+/// A recursive descent parser for maths spoken the way ClearSpeak reads it,
+/// e.g. "x squared plus two x equals zero" or "fraction one over three".
+///
+/// Words such as "over", "square root of" and "to the power of" do not say where they end.
+/// The narrow reading takes the least that follows them and the wide reading everything up to
+/// the next relation, "end fraction", "end root", "end exponent" and brackets settle it
+pub struct SpokenParser {
+    words: Vec<String>,
+    position: usize,
+    wide: bool,
+    // "over" belongs to a fraction that is already open
+    in_fraction: bool
+}
+
+impl SpokenParser {
+    fn new(spoken: &str, wide: bool) -> Self {
+        SpokenParser {
+            words: Self::tokenize(spoken),
+            position: 0,
+            wide,
+            in_fraction: false
+        }
+    }
+
+    /// The different ways the spoken maths can be read, one when it is not ambiguous.
+    /// A reading that can not be followed is dropped, the maths is ambiguous only when both can
+    pub fn parse(spoken: &str) -> Result<Vec<MathNode>, Cow<'static, str>> {
+        let narrow = SpokenParser::new(spoken, false).parse_all();
+        let wide = SpokenParser::new(spoken, true).parse_all();
+
+        match (narrow, wide) {
+            (Ok(narrow), Ok(wide)) if narrow == wide => Ok(vec![narrow]),
+            (Ok(narrow), Ok(wide)) => Ok(vec![narrow, wide]),
+            (Ok(reading), Err(_)) | (Err(_), Ok(reading)) => Ok(vec![reading]),
+            (Err(e), Err(_)) => Err(e)
+        }
+    }
+
+    // words in lower case, "2x" is split into "2" and "x"
+    fn tokenize(spoken: &str) -> Vec<String> {
+        let mut words = Vec::new();
+
+        for word in spoken.split_whitespace() {
+            let word = word.trim_matches(|c: char| c == ',' || c == '?' || c == '!').to_lowercase();
+            let digits = word.chars().take_while(|c| c.is_ascii_digit() || *c == '.').count();
+
+            if digits > 0 && digits < word.len() {
+                words.push(word[..digits].to_string());
+                words.push(word[digits..].to_string());
+            }else if !word.is_empty(){
+                words.push(word);
+            }
+        }
+
+        words
+    }
+
+    fn parse_all(mut self) -> Result<MathNode, Cow<'static, str>> {
+        if self.words.is_empty(){
+            return Err(Borrowed("There was no maths to add"));
+        }
+
+        let nodes = self.parse_relation()?;
+
+        if self.position < self.words.len() {
+            let rest = self.words[self.position..].join(" ");
+            return Err(Cow::from(format!("I could not follow \"{}\" in your maths", rest)));
+        }
+
+        Ok(MathNode::row(nodes))
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.words.get(self.position).map(|word| word.as_str())
+    }
+
+    // whether the next words are a phrase
+    fn at(&self, phrase: &str) -> bool {
+        self.at_from(self.position, phrase)
+    }
+
+    fn at_from(&self, position: usize, phrase: &str) -> bool {
+        let length = phrase.split(' ').count();
+
+        position + length <= self.words.len()
+            && self.words[position..position + length].iter()
+                .zip(phrase.split(' '))
+                .all(|(word, expected)| word == expected)
+    }
+
+    // take the phrase when it comes next
+    fn accept(&mut self, phrase: &str) -> bool {
+        if self.at(phrase){
+            self.position += phrase.split(' ').count();
+            true
+        }else{
+            false
+        }
+    }
+
+    fn expect(&mut self, phrase: &str) -> Result<(), Cow<'static, str>> {
+        if self.accept(phrase){
+            Ok(())
+        }else{
+            Err(Cow::from(format!("I expected \"{}\" in your maths", phrase)))
+        }
+    }
+
+    // whether a phrase closes the construct being read before the next relation
+    fn closes_later(&self, phrase: &str) -> bool {
+        (self.position..self.words.len())
+            .take_while(|&position| !RELATIONS.iter().any(|&(relation, _)| self.at_from(position, relation)))
+            .any(|position| self.at_from(position, phrase))
+    }
+
+    fn relation(&self) -> Option<(usize, &'static str)> {
+        RELATIONS.iter()
+            .find(|&&(phrase, _)| self.at(phrase))
+            .map(|&(phrase, operator)| (phrase.split(' ').count(), operator))
+    }
+
+    // sums joined by relations, "x plus 1 equals 2"
+    fn parse_relation(&mut self) -> Result<Vec<MathNode>, Cow<'static, str>> {
+        let mut nodes = self.parse_sum()?;
+
+        while let Some((length, operator)) = self.relation() {
+            self.position += length;
+            nodes.push(MathNode::Operator(operator.to_string()));
+            nodes.extend(self.parse_sum()?);
+        }
+
+        Ok(nodes)
+    }
+
+    // a wide reading divides the whole sum, "x plus 1 all over 2" always does
+    fn parse_sum(&mut self) -> Result<Vec<MathNode>, Cow<'static, str>> {
+        let numerator = self.parse_additive()?;
+
+        let over = self.accept("all over") || (self.wide && !self.in_fraction && self.accept("over"));
+
+        if !over {
+            return Ok(numerator);
+        }
+
+        let denominator = self.parse_additive()?;
+
+        Ok(vec![MathNode::Fraction(Box::new(MathNode::row(numerator)), Box::new(MathNode::row(denominator)))])
+    }
+
+    // terms joined by plus and minus
+    fn parse_additive(&mut self) -> Result<Vec<MathNode>, Cow<'static, str>> {
+        let mut nodes = Vec::new();
+
+        if self.accept("minus") || self.accept("negative") {
+            nodes.push(MathNode::Operator("-".to_string()));
+        }
+
+        nodes.extend(self.parse_term()?);
+
+        loop {
+            let operator = if self.accept("plus or minus") {
+                "±"
+            }else if self.accept("plus") || self.accept("+") {
+                "+"
+            }else if self.accept("minus") || self.accept("-") {
+                "-"
+            }else{
+                break;
+            };
+
+            nodes.push(MathNode::Operator(operator.to_string()));
+            nodes.extend(self.parse_term()?);
+        }
+
+        Ok(nodes)
+    }
+
+    // factors multiplied, divided or written side by side, "2 x", "a times b"
+    fn parse_term(&mut self) -> Result<Vec<MathNode>, Cow<'static, str>> {
+        let mut nodes = vec![self.parse_factor()?];
+
+        loop {
+            if self.accept("times") || self.accept("multiplied by") {
+                nodes.push(MathNode::Operator("×".to_string()));
+                nodes.push(self.parse_factor()?);
+            }else if self.accept("divided by") {
+                nodes.push(MathNode::Operator("÷".to_string()));
+                nodes.push(self.parse_factor()?);
+            }else if !self.wide && !self.in_fraction && !self.at("all over") && self.accept("over") {
+                // the narrow reading divides the term so far by the next factor
+                let numerator = MathNode::row(nodes);
+                let denominator = self.parse_factor()?;
+
+                nodes = vec![MathNode::Fraction(Box::new(numerator), Box::new(denominator))];
+            }else if self.starts_factor() {
+                nodes.push(self.parse_factor()?);
+            }else{
+                break;
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    // an atom with any powers and subscripts after it
+    fn parse_factor(&mut self) -> Result<MathNode, Cow<'static, str>> {
+        let mut node = self.parse_atom()?;
+
+        loop {
+            if self.accept("squared") {
+                node = MathNode::Power(Box::new(node), Box::new(MathNode::Number("2".to_string())));
+            }else if self.accept("cubed") {
+                node = MathNode::Power(Box::new(node), Box::new(MathNode::Number("3".to_string())));
+            }else if self.accept("to the power of") || self.accept("raised to the power of")
+                || self.accept("to the power") || self.accept("raised to the power") {
+                let exponent = self.parse_scoped("end exponent")?;
+                node = MathNode::Power(Box::new(node), Box::new(exponent));
+            }else if self.at("to the") && self.ordinal_at(self.position + 2).is_some() {
+                // "x to the fourth"
+                let exponent = self.ordinal_at(self.position + 2).unwrap();
+                self.position += 3;
+                node = MathNode::Power(Box::new(node), Box::new(MathNode::Number(exponent.to_string())));
+            }else if self.accept("sub") || self.accept("subscript") {
+                let subscript = self.parse_atom()?;
+                node = MathNode::Subscript(Box::new(node), Box::new(subscript));
+            }else{
+                return Ok(node);
+            }
+        }
+    }
+
+    // what follows "over", "root of" or "to the power of", the closing phrase makes it explicit
+    fn parse_scoped(&mut self, close: &'static str) -> Result<MathNode, Cow<'static, str>> {
+        let in_fraction = self.in_fraction;
+        self.in_fraction = false;
+
+        let node = if self.closes_later(close) {
+            let nodes = self.parse_sum()?;
+            self.expect(close)?;
+            MathNode::row(nodes)
+        }else if self.wide {
+            MathNode::row(self.parse_additive()?)
+        }else{
+            self.parse_factor()?
+        };
+
+        self.in_fraction = in_fraction;
+
+        Ok(node)
+    }
+
+    fn starts_factor(&self) -> bool {
+        match self.peek() {
+            Some(word) => {
+                self.number_value(word).is_some()
+                    || word.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false)
+                    || Self::identifier(word).is_some()
+                    || self.at("open bracket") || self.at("open paren") || self.at("bracket")
+                    || self.at("the") || self.at("fraction") || self.at("square root") || self.at("cube root")
+                    || self.at("root") || self.at("capital") || self.at("pi")
+                    || (self.ordinal_at(self.position).is_some() && self.words.get(self.position + 1).map(|w| w == "root").unwrap_or(false))
+            },
+            None => false
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<MathNode, Cow<'static, str>> {
+        self.accept("the");
+
+        if self.accept("open bracket") || self.accept("open paren") || self.accept("bracket") {
+            let in_fraction = self.in_fraction;
+            self.in_fraction = false;
+
+            let inner = self.parse_relation()?;
+
+            self.in_fraction = in_fraction;
+
+            if !(self.accept("close bracket") || self.accept("close paren") || self.accept("end bracket")) {
+                return Err(Borrowed("The bracket in your maths is not closed"));
+            }
+
+            return Ok(MathNode::Fenced(Box::new(MathNode::row(inner))));
+        }
+
+        // "fraction 1 over 3" and "fraction with numerator 1 and denominator 3"
+        if self.accept("fraction") {
+            let in_fraction = self.in_fraction;
+            self.in_fraction = true;
+
+            let (numerator, denominator_word) = if self.accept("with numerator") {
+                (self.parse_additive()?, "and denominator")
+            }else{
+                (self.parse_additive()?, "over")
+            };
+
+            self.in_fraction = in_fraction;
+            self.expect(denominator_word)?;
+
+            let denominator = self.parse_scoped("end fraction")?;
+
+            return Ok(MathNode::Fraction(Box::new(MathNode::row(numerator)), Box::new(denominator)));
+        }
+
+        let index = if self.accept("square root") {
+            Some(None)
+        }else if self.accept("cube root") {
+            Some(Some(3))
+        }else if self.ordinal_at(self.position).is_some() && self.words.get(self.position + 1).map(|w| w == "root").unwrap_or(false) {
+            let index = self.ordinal_at(self.position);
+            self.position += 2;
+            Some(index)
+        }else if self.accept("root") {
+            Some(None)
+        }else{
+            None
+        };
+
+        if let Some(index) = index {
+            self.accept("of");
+
+            let radicand = self.parse_scoped("end root")?;
+            let index = index.map(|index| Box::new(MathNode::Number(index.to_string())));
+
+            return Ok(MathNode::Root(Box::new(radicand), index));
+        }
+
+        if self.accept("capital") {
+            return match self.peek().and_then(Self::identifier) {
+                Some(letter) => {
+                    self.position += 1;
+                    Ok(MathNode::Identifier(letter.to_uppercase()))
+                },
+                None => Err(Borrowed("Say a letter after capital"))
+            };
+        }
+
+        if let Some(number) = self.parse_number()? {
+            return Ok(MathNode::Number(number));
+        }
+
+        if let Some(identifier) = self.peek().and_then(Self::identifier) {
+            self.position += 1;
+            return Ok(MathNode::Identifier(identifier));
+        }
+
+        match self.peek() {
+            Some(word) => Err(Cow::from(format!("I could not follow \"{}\" in your maths", word))),
+            None => Err(Borrowed("Your maths ended too early"))
+        }
+    }
+
+    // digits as recognised, or number words such as "twenty three point five"
+    fn parse_number(&mut self) -> Result<Option<String>, Cow<'static, str>> {
+        let word = match self.peek() {
+            Some(word) => word.to_string(),
+            None => return Ok(None)
+        };
+
+        if word.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+            self.position += 1;
+            return Ok(Some(word));
+        }
+
+        let mut total: u32 = 0;
+        let mut current: u32 = 0;
+        let mut found = false;
+        let mut digit = false;
+
+        // numbers that do not fit, such as "one hundred hundred hundred hundred hundred", are refused
+        while let Some(word) = self.peek() {
+            let value = self.number_value(word);
+
+            if let Some(value) = value.filter(|&value| digit && value < 10) {
+                // digits said one after another, "one two three" is 123
+                current = current.checked_mul(10).and_then(|tens| tens.checked_add(value)).ok_or(TOO_LARGE)?;
+            }else if let Some(value) = value {
+                current = current.checked_add(value).ok_or(TOO_LARGE)?;
+            }else if word == "hundred" && found {
+                current = current.checked_mul(100).ok_or(TOO_LARGE)?;
+            }else if word == "thousand" && found {
+                total = current.checked_mul(1000).and_then(|thousands| total.checked_add(thousands)).ok_or(TOO_LARGE)?;
+                current = 0;
+            }else if word == "and" && found && self.words.get(self.position + 1).and_then(|next| self.number_value(next)).is_some() {
+                // "one hundred and five"
+            }else{
+                break;
+            }
+
+            digit = value.map(|value| value < 10).unwrap_or(false);
+            found = true;
+            self.position += 1;
+        }
+
+        if !found {
+            return Ok(None);
+        }
+
+        let mut number = total.checked_add(current).ok_or(TOO_LARGE)?.to_string();
+
+        if self.at("point") && self.words.get(self.position + 1).and_then(|next| self.number_value(next)).map(|v| v < 10).unwrap_or(false) {
+            self.position += 1;
+            number.push('.');
+
+            while let Some(digit) = self.peek().and_then(|word| self.number_value(word)).filter(|&digit| digit < 10) {
+                number.push_str(&digit.to_string());
+                self.position += 1;
+            }
+        }
+
+        Ok(Some(number))
+    }
+
+    fn number_value(&self, word: &str) -> Option<u32> {
+        UNITS.iter().position(|&unit| unit == word).map(|value| value as u32)
+            .or_else(|| TENS.iter().position(|&tens| !tens.is_empty() && tens == word).map(|value| value as u32 * 10))
+    }
+
+    fn ordinal_at(&self, position: usize) -> Option<u32> {
+        let word = self.words.get(position)?;
+
+        ORDINALS.iter().position(|&ordinal| !ordinal.is_empty() && ordinal == word).map(|value| value as u32)
+    }
+
+    // letters and greek letters, with the names speech recognition gives some letters
+    fn identifier(word: &str) -> Option<String> {
+        if let Some(&(_, _, symbol)) = GREEK.iter().find(|&&(spoken, _, _)| spoken == word) {
+            return Some(symbol.to_string());
+        }
+
+        let letter = match word {
+            "ex" => "x",
+            "why" => "y",
+            "zed" | "zee" => "z",
+            word if word.chars().count() == 1 && word.chars().all(char::is_alphabetic) => word,
+            _ => return None
+        };
+
+        Some(letter.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latex(spoken: &str) -> Vec<String> {
+        SpokenParser::parse(spoken).unwrap().iter().map(MathNode::to_latex).collect()
+    }
+
+    #[test]
+    fn numbers(){
+        assert_eq!(latex("twenty three point five"), vec!["23.5"]);
+        assert_eq!(latex("one hundred and five"), vec!["105"]);
+        assert_eq!(latex("two thousand three hundred"), vec!["2300"]);
+    }
+
+    #[test]
+    fn large_numbers_are_refused(){
+        assert_eq!(SpokenParser::parse("one hundred hundred hundred hundred hundred"), Err(TOO_LARGE));
+        assert_eq!(SpokenParser::parse("ninety hundred hundred hundred thousand"), Err(TOO_LARGE));
+    }
+
+    #[test]
+    fn ambiguous_fraction(){
+        assert_eq!(latex("x over two plus one"), vec!["\\frac{x}{2} + 1", "\\frac{x}{2 + 1}"]);
+        assert_eq!(latex("fraction x over two plus one end fraction"), vec!["\\frac{x}{2 + 1}"]);
+    }
+
+    #[test]
+    fn unambiguous(){
+        assert_eq!(latex("x squared plus two x equals zero"), vec!["{x}^{2} + 2 x = 0"]);
+        assert_eq!(latex("square root of x end root"), vec!["\\sqrt{x}"]);
+    }
+
+    #[test]
+    fn errors(){
+        assert!(SpokenParser::parse("").is_err());
+        assert!(SpokenParser::parse("x plus").is_err());
+    }
+
+    #[test]
+    fn digits_said_in_turn(){
+        assert_eq!(latex("one two three"), vec!["123"]);
+        assert_eq!(latex("x equals four two"), vec!["x = 42"]);
+        assert_eq!(latex("one hundred two"), vec!["102"]);
+        assert_eq!(SpokenParser::parse("nine nine nine nine nine nine nine nine nine nine nine"), Err(TOO_LARGE));
+    }
+
+    #[test]
+    fn greek_letters(){
+        assert_eq!(latex("alpha plus pi"), vec!["\\alpha + \\pi"]);
+        assert_eq!(latex("x is less than infinity"), vec!["x < \\infty"]);
+    }
+}
@@ -1,41 +1,100 @@
 mod builder;
 mod interface;
+mod math;
+mod answer;
+mod dictation;
+mod history;
+mod visits;
 pub mod intents;
 
-use std::collections::HashMap;
 use std::borrow::Cow::{Borrowed, self};
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
 
-use interface::{Node, Predicate, NodeIndex, NodeData, predicates, Note};
+use interface::{Node, Predicate, NodeIndex, NodeData, LabelIndex, predicates, plural};
+pub use interface::{PaperMetadata, NodeStatus, Status, Note, Choices, ChoiceOption};
+pub use answer::{Answer, AnswerMath, Draft, TextUnit, TextPosition};
+use math::{MathNode, spoken::SpokenParser};
+use history::{History, Snapshot};
+use visits::Visits;
+use answer::spell;
+pub use dictation::Locale;
 
-use Reference::{Start, Current, End};
+use Reference::{Start, Current, End, Label, Range};
 
 // re exports
-pub use builder::{QPaperBuilder, Builder};
-pub use intents::{Read, Write, Reference, Intent, Reader, Writer, WriteResult, ReadResult, IntentResult, MetaIntent};
+pub use builder::{QPaperBuilder, Builder, BuildError, BuildErrors};
+pub use intents::{Read, Write, BulkAction, AnswerEdit, ChoiceEdit, AnswerRead, AnswerReader, Visit, NoteRead, NoteRef, NoteReader, CountTarget, CountResult, Reference, Filter, Intent, Reader, Writer, WriteResult, ReadResult, ReadListResult, IntentResult, MetaIntent};
 
 #[derive(Debug, Clone)]
 pub struct QuestionPaper {
     pub nodes: Vec<Node>,
     prev_index: usize,
+    // the nodes visited before the current one and those gone back from
+    visits: Visits,
     last_index: usize,
     total_questions: u32,
-    marked: HashMap<usize, NodeData>,
-    skipped: HashMap<usize, NodeData>,
-    notes: Vec<Note>
+    // the status of each node, indexed like nodes
+    statuses: Vec<NodeStatus>,
+    notes: Vec<Note>,
+    // the note last taken or read, "that note"
+    last_note: Option<usize>,
+    // answers by question node
+    answers: BTreeMap<usize, Answer>,
+    // rough work by question node, it is not marked
+    rough_work: BTreeMap<usize, Answer>,
+    // the question dictation goes to and whether it goes to the answer or the rough work
+    answering: Option<usize>,
+    drafting: Draft,
+    // readings of ambiguous maths waiting for the candidate to choose, with where they go
+    pending_math: Option<(usize, Draft, Vec<MathNode>)>,
+    // changes to the candidate's work that can be undone and redone
+    history: History,
+    metadata: PaperMetadata,
+    labels: LabelIndex
+}
+
+/// The candidate's progress through a paper, saved and restored with the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub prev_index: usize,
+    pub statuses: Vec<NodeStatus>,
+    pub notes: Vec<Note>,
+    #[serde(default)]
+    pub answers: BTreeMap<usize, Answer>,
+    #[serde(default)]
+    pub answering: Option<usize>,
+    /// rough work is kept apart from the answers as it is not marked
+    #[serde(default)]
+    pub rough_work: BTreeMap<usize, Answer>,
+    #[serde(default)]
+    pub drafting: Draft
 }
 
 
 
 impl QuestionPaper {
-    pub fn new(nodes: Vec<Node>, last_index: usize, total_questions: u32) -> Self {
+    pub fn new(nodes: Vec<Node>, last_index: usize, total_questions: u32, metadata: PaperMetadata) -> Self {
+        let statuses = vec![NodeStatus::default(); nodes.len()];
+        let labels = LabelIndex::new(&nodes);
+
         QuestionPaper {
             nodes,
             prev_index:0,
+            visits: Visits::default(),
             last_index,
             total_questions,
-            marked: HashMap::new(),
-            skipped: HashMap::new(),
-            notes: vec![]
+            statuses,
+            notes: vec![],
+            last_note: None,
+            answers: BTreeMap::new(),
+            rough_work: BTreeMap::new(),
+            answering: None,
+            drafting: Draft::Answer,
+            pending_math: None,
+            history: History::default(),
+            metadata,
+            labels
         }
     }
 
@@ -68,19 +127,53 @@ impl QuestionPaper {
         self.last_index 
     }
 
+    // visit the nodes of a list read, the candidate carries on from the last of them
+    fn read_list(&mut self, result: ReadListResult) -> IntentResult {
+        let result = result.map(|nodes| {
+            for node in &nodes {
+                self.statuses[node.index].visited_at = Some(NodeStatus::now());
+            }
+
+            if let Some(last) = nodes.last() {
+                self.update_previous(last.index);
+            }
+
+            nodes.into_iter().map(|node| node.data).collect()
+        });
+
+        IntentResult::ReadList(result)
+    }
+
+    /// Go to a node, the node left is kept to go back to
     pub fn update_previous(&mut self, index: usize) {
+        if index != self.prev_index {
+            self.visits.leave(self.prev_index);
+        }
+
         self.prev_index = index;
     }
 
     // resolve a read or write intent
     pub fn resolve_intent(&mut self, intent: Intent) ->  IntentResult {
         match intent {
+            Intent::ReadIntent(ref read_intent) if read_intent.is_range() => {
+                let result = self.find_nodes(std::slice::from_ref(read_intent));
+
+                self.read_list(result)
+            },
+            // a column is read down the whole table until a row is chosen
+            Intent::ReadIntent(Read::TableColumn(ref column)) if self.current_row().is_none() && self.current_table().is_some() => {
+                let result = self.resolve_column(column);
+
+                self.read_list(result)
+            },
             Intent::ReadIntent(ref read_intent) => {
                 let result = match self.resolve_read_intent(read_intent) {
                     Ok(node) => {
                         let index = node.index;
         
                         self.update_previous(index);
+                        self.statuses[index].visited_at = Some(NodeStatus::now());
         
                         Ok(node.data.clone())
                     },
@@ -89,6 +182,9 @@ impl QuestionPaper {
 
                 IntentResult::Read(result)
             },
+            Intent::AnswerIntent(ref read) => IntentResult::Answer(self.read_answer(read)),
+            Intent::NoteIntent(ref read) => IntentResult::Notes(self.read_notes(read)),
+            Intent::VisitIntent(visit) => self.visit(visit),
             Intent::WriteIntent(ref write_intent) => {
                 let result = self.resolve_write_intent(write_intent);
 
@@ -98,12 +194,25 @@ impl QuestionPaper {
                 match meta {
                     MetaIntent::Marked => {
                         // get the total marked and read them out
-                        let total_marked = self.marked.len();
+                        let total_marked = self.num_marked();
 
                         return IntentResult::Meta(format!("You have {} marked questions", total_marked));
                     },
-                    MetaIntent::Skipped => {
-                        return IntentResult::Meta(format!("You have skipped {} question", self.skipped.len()));
+                    MetaIntent::Skipped => IntentResult::Meta(format!("You have skipped {} question", self.num_skipped())),
+                    MetaIntent::Paper => IntentResult::Meta(self.metadata.describe()),
+                    MetaIntent::Duration => IntentResult::Meta(self.metadata.describe_duration()),
+                    MetaIntent::TotalMarks => IntentResult::Meta(self.metadata.describe_total_marks()),
+                    MetaIntent::Progress => {
+                        let section = self.section_of(self.prev_index())
+                            .map(|section| &self.nodes[section].data);
+
+                        match section {
+                            Some(NodeData::Section(ref section)) => IntentResult::Meta(section.describe()),
+                            _ => IntentResult::Meta("You have not started a section yet".to_string())
+                        }
+                    },
+                    MetaIntent::Count(target, ref scope, ref filters) => {
+                        IntentResult::Count(self.count(*target, scope, filters))
                     }
                 }
             }
@@ -111,9 +220,37 @@ impl QuestionPaper {
         
     }
 
+    // go back or forward through the nodes visited, or say where the candidate was before
+    fn visit(&mut self, visit: Visit) -> IntentResult {
+        let current = self.prev_index();
+
+        let found = match visit {
+            Visit::Back => self.visits.back(current),
+            Visit::Forward => self.visits.forward(current),
+            Visit::Before => {
+                return IntentResult::Meta(match self.visits.previous() {
+                    Some(index) => format!("Before this you were on {}", self.nodes[index].data.name()),
+                    None => "You have not been anywhere else yet".to_string()
+                });
+            }
+        };
+
+        let index = match found {
+            Some(index) => index,
+            None if visit == Visit::Back => return IntentResult::Read(Err(Borrowed("There is nowhere to go back to"))),
+            None => return IntentResult::Read(Err(Borrowed("There is nowhere to go forward to")))
+        };
+
+        // moving through the history is not a visit of its own
+        self.prev_index = index;
+        self.statuses[index].visited_at = Some(NodeStatus::now());
+
+        IntentResult::Read(Ok(self.nodes[index].data.clone()))
+    }
+
     /// Check how many questions have been marked for review
     pub fn num_marked(&self) -> usize {
-        self.marked.len()
+        self.statuses.iter().filter(|status| status.is_marked()).count()
     }
 
     pub fn total_questions(&self) -> u32 {
@@ -121,7 +258,68 @@ impl QuestionPaper {
     }
 
     pub fn num_skipped(&self) -> usize {
-        self.skipped.len()
+        self.statuses.iter().filter(|status| status.is_skipped()).count()
+    }
+
+    /// The answer to a question node
+    pub fn answer(&self, index: usize) -> Option<&Answer> {
+        self.answers.get(&index)
+    }
+
+    /// The rough work for a question node
+    pub fn rough_work(&self, index: usize) -> Option<&Answer> {
+        self.rough_work.get(&index)
+    }
+
+    /// The status of a node
+    pub fn status(&self, index: usize) -> Option<&NodeStatus> {
+        self.statuses.get(index)
+    }
+
+    /// A snapshot of the candidate's progress
+    pub fn session(&self) -> Session {
+        Session {
+            prev_index: self.prev_index,
+            statuses: self.statuses.clone(),
+            notes: self.notes.clone(),
+            answers: self.answers.clone(),
+            answering: self.answering,
+            rough_work: self.rough_work.clone(),
+            drafting: self.drafting
+        }
+    }
+
+    /// Restore progress saved from a session on this same paper
+    pub fn restore_session(&mut self, session: Session) -> Result<(), Cow<'static, str>> {
+        let is_question = |index: &usize| self.nodes.get(*index).map(|node| node.data.is_question()).unwrap_or(false);
+
+        if session.statuses.len() != self.nodes.len()
+            || session.prev_index >= self.nodes.len()
+            || !session.answers.keys().all(is_question)
+            || !session.rough_work.keys().all(is_question)
+            || !session.answering.iter().all(is_question) {
+            return Err(Borrowed("The session was saved on a different paper"));
+        }
+
+        self.prev_index = session.prev_index;
+        self.visits.clear();
+        self.statuses = session.statuses;
+        self.notes = session.notes;
+        self.last_note = None;
+        self.answers = session.answers;
+        self.answering = session.answering;
+        self.rough_work = session.rough_work;
+        self.drafting = session.drafting;
+        self.pending_math = None;
+        self.history.clear();
+        self.refresh_statuses();
+
+        Ok(())
+    }
+
+    /// Paper wide information such as the title and duration
+    pub fn metadata(&self) -> &PaperMetadata {
+        &self.metadata
     }
 
     /// get the notes
@@ -129,190 +327,2360 @@ impl QuestionPaper {
         &self.notes
     }
 
-    // resolve the read intent in 
-    fn find_node(&mut self, reads: &Vec<Read>) -> ReadResult {
-        let mut node = None;
+    // the section a node belongs to, a section belongs to itself
+    fn section_of(&self, index: usize) -> Option<usize> {
+        self.nth(index)?
+            .closest(&predicates::SectionPredicate)
+            .map(|section| section.index())
+    }
+
+    // the work a write can change, kept before each write so that it can be undone
+    fn snapshot(&self) -> Snapshot {
+        // visits belong to navigation, undoing a write leaves them as they are
+        let statuses = self.statuses.iter()
+            .map(|status| NodeStatus { visited_at: None, ..status.clone() })
+            .collect();
 
-        for intent in reads {
-            node = Some(self.resolve_read_intent(intent));
+        Snapshot {
+            statuses,
+            notes: self.notes.clone(),
+            answers: self.answers.clone(),
+            rough_work: self.rough_work.clone(),
+            answering: self.answering,
+            drafting: self.drafting
         }
+    }
 
-        if node.is_none(){
-            return Err(Borrowed("Could not find the specified request"));
+    // put back work kept by an undo or redo
+    fn restore(&mut self, snapshot: Snapshot) {
+        let mut statuses = snapshot.statuses;
+
+        for (status, current) in statuses.iter_mut().zip(&self.statuses) {
+            status.visited_at = current.visited_at;
         }
 
-        node.unwrap()
+        self.statuses = statuses;
+        self.notes = snapshot.notes;
+        self.answers = snapshot.answers;
+        self.rough_work = snapshot.rough_work;
+        self.answering = snapshot.answering;
+        self.drafting = snapshot.drafting;
+        // positions and readings may no longer match the work
+        self.last_note = None;
+        self.pending_math = None;
+
+        self.refresh_statuses();
     }
 
-}
+    // bring the question flags and section statistics in line with the statuses
+    fn refresh_statuses(&mut self) {
+        for index in 0..self.nodes.len() {
+            let marked = self.statuses[index].is_marked();
 
-impl Reader for QuestionPaper {
-    /// Resolves a read intent
-    fn resolve_read_intent(&mut self, read_intent: &Read) -> ReadResult {
-        match read_intent {
-            Read::Question(ref question) => self.resolve_question(question),
-            Read::Section(ref section) => self.resolve_section(section),
+            match self.nodes[index].data {
+                NodeData::Question(ref mut question) => question.marked = marked,
+                NodeData::Section(_) => self.refresh_section(index),
+                _ => ()
+            }
         }
     }
 
-    /// Resolve a question
-    fn resolve_question(&mut self, reference: &Reference) -> ReadResult {
-        let predicate = predicates::QuestionPredicate;
+    // recount the statistics of the section a node belongs to
+    fn refresh_section(&mut self, index: usize) {
+        let section = match self.section_of(index) {
+            Some(section) => section,
+            None => return
+        };
 
-        self.resolve_referece(reference, predicate)
+        let questions = self.questions_in(section);
+
+        let count = |check: fn(&NodeStatus) -> bool| {
+            questions.iter().filter(|&&index| check(&self.statuses[index])).count() as u32
+        };
+
+        let marked = count(NodeStatus::is_marked);
+        let skipped = count(NodeStatus::is_skipped);
+        let answered = count(NodeStatus::is_answered);
+        // skipped questions that were answered later are no longer outstanding
+        let skipped_unanswered = count(|status| status.is_skipped() && !status.is_answered());
+
+        if let NodeData::Section(ref mut data) = self.nodes[section].data {
+            data.num_of_questions = questions.len() as u32;
+            data.num_of_marked = marked;
+            data.num_of_skipped = skipped;
+            data.num_of_attempted = answered;
+            data.num_of_remaining = data.num_of_questions
+                .saturating_sub(answered)
+                .saturating_sub(skipped_unanswered);
+        }
     }
 
-    /// Resolve a section
-    fn resolve_section(&mut self, reference: &Reference) -> ReadResult {
-        let predicate = predicates::SectionPredicate;
+    // find a question by its printed label, preferring the current section
+    fn resolve_label(&self, label: &str) -> ReadResult {
+        let current_section = self.section_of(self.prev_index());
+        let questions = self.labels.questions(label);
 
-        self.resolve_referece(reference, predicate)
+        // labels repeat when numbering restarts in each section
+        let found = questions.iter()
+            .find(|&&index| self.section_of(index) == current_section)
+            .or_else(|| questions.first());
+
+        match found {
+            Some(&index) => Ok(self.nodes[index].clone()),
+            None => Err(Cow::from(format!("There is no question {}", label)))
+        }
     }
 
-    /// Resolve from a reference
-    fn resolve_referece<P: Predicate>(&mut self, reference: &Reference, predicate: P) -> ReadResult {
+    // the indices of the children of a node in document order
+    fn children_of(&self, index: usize) -> Vec<usize> {
+        self.nth(index)
+            .map(|node| node.children().map(|child| child.index()).collect())
+            .unwrap_or_default()
+    }
 
-        let (prev, skip) = match reference {
-            Start(skip) => (0, skip.abs() as usize),
-            Current(skip) => (self.prev_index(), (skip.abs() as usize) + 1),
-            End(skip) => (self.last_index(), skip.abs() as usize)
-            
-        };
-        
+    // the questions of a section in document order
+    fn questions_in(&self, section: usize) -> Vec<usize> {
+        self.nth(section)
+            .map(|node| node.children()
+                .filter(|child| child.data().is_question())
+                .map(|child| child.index())
+                .collect())
+            .unwrap_or_default()
+    }
 
-        self.resolve(predicate, prev, skip, reference)
+    // the table being read, either directly or through one of its rows, cells or its question
+    fn current_table(&self) -> Option<usize> {
+        let current = self.nth(self.prev_index())?;
+
+        match current.data() {
+            NodeData::Table(_) => Some(current.index()),
+            NodeData::Question(_) => current.children()
+                .find(|child| child.data().is_table())
+                .map(|table| table.index()),
+            NodeData::TableRow(_) | NodeData::TableCell(_) => current
+                .closest(&predicates::TablePredicate)
+                .map(|table| table.index()),
+            _ => None
+        }
     }
 
-    fn resolve<P: Predicate>(&mut self, predicate: P, prev: usize, skip: usize, reference: &Reference) -> ReadResult {
-        let finder = self.find(predicate, prev, skip);
+    // the table row being read
+    fn current_row(&self) -> Option<usize> {
+        let current = self.prev_index();
+
+        match self.nodes.get(current).map(|node| &node.data) {
+            Some(NodeData::TableRow(_)) => Some(current),
+            Some(NodeData::TableCell(_)) => self.nodes[current].parent,
+            _ => None
+        }
+    }
 
+    // a cell in every row of the table being read
+    fn resolve_column(&self, reference: &Reference) -> ReadListResult {
+        let table = match self.current_table() {
+            Some(table) => table,
+            None => return Err(Borrowed("Choose a table first"))
+        };
 
-        if reference.is_forward(){
-            self.find_next(finder)
+        let mut cells = Vec::new();
+        let mut error = None;
+
+        for row in self.children_of(table) {
+            if !matches!(self.nodes[row].data, NodeData::TableRow(_)) {
+                continue;
+            }
+
+            // short rows are left out
+            match self.resolve_child(row, None, reference) {
+                Ok(cell) => cells.push(cell),
+                Err(e) => error = error.or(Some(e))
+            }
+        }
+
+        if cells.is_empty() {
+            Err(error.unwrap_or(Borrowed("The table has no rows")))
         }else{
-            self.find_back(finder)
+            Ok(cells)
         }
     }
 
-    /// Do a foward find
-    fn find_next<P: Predicate>(&self, mut finder: Find<P>) -> ReadResult {
-        if let Some(node) = finder.next(){
-            Ok(node.raw().clone())
-        }else{
-            Err(Borrowed("Could not find a next node"))
+    // resolve a reference among the children of a node
+    fn resolve_child(&self, parent: usize, current: Option<usize>, reference: &Reference) -> ReadResult {
+        let children = self.children_of(parent);
+
+        self.resolve_among(&children, current, reference)
+    }
+
+    // resolve a reference among a list of sibling nodes
+    fn resolve_among(&self, children: &[usize], current: Option<usize>, reference: &Reference) -> ReadResult {
+        let position = match reference {
+            Start(n) => *n as i64 - 1,
+            // the last item is one from the end, as it is for the whole paper
+            End(n) => children.len() as i64 - n.abs() as i64,
+            Current(offset) => {
+                let position = current.and_then(|current| children.iter().position(|&child| child == current));
+
+                match position {
+                    Some(position) => position as i64 + *offset as i64,
+                    // nothing chosen yet, "next" starts from the first child
+                    None => (*offset as i64 - 1).max(0)
+                }
+            },
+            Label(_) => return Err(Borrowed("Only questions can be found by their label")),
+            Range(_, _) => return Err(Borrowed("A range cannot be read as a single item"))
+        };
+
+        if position < 0 || position as usize >= children.len() {
+            return Err(Borrowed("There is nothing at that position"));
         }
+
+        Ok(self.nodes[children[position as usize]].clone())
     }
 
-    /// Do a reverse find
-    fn find_back<P: Predicate>(&self, mut finder: Find<P>) -> ReadResult {
-        if let Some(node) = finder.next_back(){
-            Ok(node.raw().clone())
-        }else{
-            Err(Borrowed("Could not resolve a previous node"))
+    // questions that pass every filter
+    fn question_predicate(&self, filters: &[Filter]) -> Result<Box<dyn Predicate>, Cow<'static, str>> {
+        let mut predicate: Box<dyn Predicate> = Box::new(predicates::QuestionPredicate);
+
+        for filter in filters {
+            predicate = Box::new(predicate.and(self.filter_predicate(filter)?));
         }
+
+        Ok(predicate)
     }
 
-}
+    // the predicate a read filter stands for
+    fn filter_predicate(&self, filter: &Filter) -> Result<Box<dyn Predicate>, Cow<'static, str>> {
+        let predicate: Box<dyn Predicate> = match filter {
+            Filter::Marked => Box::new(predicates::MarkedPredicate),
+            Filter::Skipped => Box::new(predicates::SkippedPredicate),
+            Filter::Unanswered => Box::new(predicates::UnansweredPredicate),
+            Filter::Visited => Box::new(predicates::VisitedPredicate),
+            Filter::OnPage(page_number) => Box::new(predicates::OnPagePredicate(*page_number)),
+            Filter::InSection => match self.section_of(self.prev_index()) {
+                Some(section) => Box::new(predicates::InSectionPredicate(section)),
+                None => return Err(Borrowed("You are not in a section yet"))
+            },
+            Filter::HasNote => Box::new(predicates::HasNotePredicate),
+            Filter::Contains(ref text) => Box::new(predicates::TextContainsPredicate(text.clone())),
+            Filter::AfterCurrent => Box::new(predicates::AfterPredicate(self.prev_index())),
+            Filter::Not(ref filter) => Box::new(self.filter_predicate(filter)?.not())
+        };
 
-impl Writer for QuestionPaper {
-    /// Resolve a write intent
-    fn resolve_write_intent(&mut self, write_intent: &Write) ->  WriteResult{
-        match write_intent {
-            Write::Mark(ref read_intents) => return self.mark_for_review(read_intents),
-            Write::Skip(ref read_intents) => self.skip(read_intents),
-            Write::Note(ref read_intents, note) => self.note(read_intents, note.to_string())
+        Ok(predicate)
+    }
+
+    // the kind of node a read intent looks for, with its reference
+    fn read_predicate<'r>(&self, read: &'r Read) -> Result<(Box<dyn Predicate>, &'r Reference), Cow<'static, str>> {
+        let predicate: Box<dyn Predicate> = match read {
+            Read::Question(_) | Read::QuestionInSection(_) => Box::new(predicates::QuestionPredicate),
+            Read::QuestionWhere(_, ref filters) => self.question_predicate(filters)?,
+            Read::Section(_) => Box::new(predicates::SectionPredicate),
+            Read::Passage(_) => Box::new(predicates::PassagePredicate),
+            Read::Paragraph(_) => Box::new(predicates::ParagraphPredicate),
+            Read::Figure(_) => Box::new(predicates::FigurePredicate),
+            Read::Table(_) => Box::new(predicates::TablePredicate),
+            Read::TableRow(_) => Box::new(predicates::TableRowPredicate),
+            Read::TableColumn(_) => Box::new(predicates::TableCellPredicate),
+            Read::Page(_) => Box::new(predicates::PagePredicate),
+            Read::Scoped(_) => return Err(Borrowed("Could not understand the request"))
+        };
+
+        match read.reference() {
+            Some(reference) => Ok((predicate, reference)),
+            None => Err(Borrowed("Could not understand the request"))
         }
     }
 
+    // resolve a read intent among the nodes below a scope, or printed on it when the scope is a page
+    fn resolve_in_scope(&self, scope: usize, read: &Read) -> ReadResult {
+        let (predicate, reference) = self.read_predicate(read)?;
+        let within = predicates::WithinPredicate(scope);
 
-    // process a read intent and mark it for review
-    fn mark_for_review(&mut self, reads: &Vec<Read>) -> WriteResult {
-        if let Ok(node) = self.find_node(reads) {
-            self.marked.insert(node.index, node.data.clone());
-            
+        let candidates: Vec<usize> = self.nodes.iter()
+            .filter_map(|node| self.nth(node.index))
+            .filter(|node| within.matches(node) && predicate.matches(node))
+            .map(|node| node.index())
+            .collect();
 
-            return WriteResult::Success("Question has been marked for review".to_string());
+        if let Label(ref label) = reference {
+            if let Read::Question(_) = read {
+                return self.labels.questions(label).iter()
+                    .find(|index| candidates.contains(index))
+                    .map(|&index| self.nodes[index].clone())
+                    .ok_or_else(|| Cow::from(format!("There is no question {} there", label)));
+            }
+        }
+
+        let current = self.nth(self.prev_index())
+            .and_then(|node| node.closest(&predicate))
+            .map(|node| node.index());
+
+        self.resolve_among(&candidates, current, reference)
+    }
+
+    // resolve a read, scoped by the node found for the reads before it
+    fn resolve_scoped(&mut self, scope: Option<usize>, read: &Read) -> ReadResult {
+        match scope {
+            Some(scope) => self.resolve_in_scope(scope, read),
+            None => self.resolve_read_intent(read)
         }
-        
-        return WriteResult::Error(Borrowed("Could not mark the specified item for review. Please try again"));        
     }
 
-    fn skip(&mut self, reads: &Vec<Read>) -> WriteResult {
-        if let Ok(node) = self.find_node(reads) {
-            self.skipped.insert(node.index, node.data.clone());
+    // resolve reads that may end in a range into the nodes they address in document order
+    fn find_nodes(&mut self, reads: &[Read]) -> ReadListResult {
+        // a scoped read carries its chain inside it
+        let reads = match reads {
+            [Read::Scoped(ref chain)] => chain.as_slice(),
+            _ => reads
+        };
+
+        let (last, outer) = match reads.split_last() {
+            Some(split) => split,
+            None => return Err(Borrowed("Could not find the specified request"))
+        };
+
+        let (from, to) = match last.reference() {
+            Some(Range(ref from, ref to)) => (from.as_ref().clone(), to.as_ref().clone()),
+            _ => return self.find_node(reads).map(|node| vec![node])
+        };
+
+        let scope = if outer.is_empty() {
+            None
+        }else{
+            Some(self.find_node(outer)?.index)
+        };
+
+        let first = self.resolve_scoped(scope, &last.with_reference(from))?.index;
+        let end = self.resolve_scoped(scope, &last.with_reference(to))?.index;
+        let (first, end) = if first <= end { (first, end) } else { (end, first) };
+
+        let (predicate, _) = self.read_predicate(last)?;
+
+        let nodes = (first..=end)
+            .filter_map(|index| self.nth(index))
+            .filter(|node| predicate.matches(node))
+            .filter(|node| scope.map(|scope| predicates::WithinPredicate(scope).matches(node)).unwrap_or(true))
+            .map(|node| node.raw().clone())
+            .collect();
+
+        Ok(nodes)
+    }
 
-            return WriteResult::Success("Question has been skipped".to_string());
+    // the kind of node a count looks for
+    fn count_predicate(target: CountTarget) -> Box<dyn Predicate> {
+        match target {
+            CountTarget::Questions => Box::new(predicates::QuestionPredicate),
+            CountTarget::Sections => Box::new(predicates::SectionPredicate),
+            CountTarget::Pages => Box::new(predicates::PagePredicate),
+            CountTarget::Passages => Box::new(predicates::PassagePredicate),
+            CountTarget::Figures => Box::new(predicates::FigurePredicate),
+            CountTarget::Tables => Box::new(predicates::TablePredicate)
         }
-        
-        return WriteResult::Error(Borrowed("Could not skip the specified item. Please try again"));        
     }
 
-    /// Take a note on this node
-    fn note(&mut self, reads: &Vec<Read>, note: String) -> WriteResult {
-        if let Ok(node) = self.find_node(reads){
-            self.notes.push(Note {
-                note,
-                index: node.index
-            });
-           
-            return WriteResult::Success("A not has been taken".to_string());
+    // count the nodes of a kind in a scope, with and without the filters
+    fn count(&mut self, target: CountTarget, scope: &[Read], filters: &[Filter]) -> Result<CountResult, Cow<'static, str>> {
+        let scope = if scope.is_empty() {
+            None
+        }else{
+            Some(self.find_node(scope)?.index)
+        };
+
+        let kind = Self::count_predicate(target);
+
+        // "in this section" is the scope of the count as well as a filter
+        let in_section = filters.contains(&Filter::InSection);
+        let scope = match scope {
+            None if in_section => self.section_of(self.prev_index()),
+            scope => scope
+        };
+
+        let mut filtered = Self::count_predicate(target);
+
+        for filter in filters {
+            filtered = Box::new(filtered.and(self.filter_predicate(filter)?));
         }
 
-        return WriteResult::Error(Borrowed("Could not take a note as requested"));
+        let in_scope: Vec<NodeIndex> = (0..self.len())
+            .filter_map(|index| self.nth(index))
+            .filter(|node| scope.map(|scope| predicates::WithinPredicate(scope).matches(node)).unwrap_or(true))
+            .collect();
 
-        
+        let total = in_scope.iter().filter(|node| kind.matches(node)).count() as u32;
+        let count = in_scope.iter().filter(|node| filtered.matches(node)).count() as u32;
+
+        let scope = scope.map(|scope| match self.nodes[scope].data {
+            NodeData::Section(ref section) if !section.section_name.is_empty() => section.section_name.clone(),
+            NodeData::Section(_) => "this section".to_string(),
+            NodeData::Page(ref page) => format!("page {}", page.page_number),
+            NodeData::Question(ref question) => format!("question {}", question.label),
+            _ => "this part".to_string()
+        });
+
+        Ok(CountResult {
+            target,
+            count,
+            total,
+            scope
+        })
     }
-}
 
+    // the question an answer is for, the question being answered or read when none is named
+    fn answer_target(&mut self, reads: &[Read]) -> Result<usize, Cow<'static, str>> {
+        let node = if !reads.is_empty() {
+            self.find_node(reads)?.index
+        }else if let Some(question) = self.answering {
+            return Ok(question);
+        }else{
+            self.prev_index()
+        };
 
-pub struct Find<'a, P:Predicate> {
-    predicate: P,
-    next: usize,
-    question_paper: &'a QuestionPaper,
-    skip: usize
-}
+        self.nth(node)
+            .and_then(|node| node.closest(&predicates::QuestionPredicate))
+            .map(|question| question.index())
+            .ok_or(Borrowed("Choose a question to answer first"))
+    }
 
-impl <'a, P: Predicate> Iterator for Find<'a, P> {
-    type Item = NodeIndex<'a>;
+    // add the reading of ambiguous maths the candidate chose
+    fn confirm_math(&mut self, choice: usize) -> WriteResult {
+        let (question, draft, mut readings) = match self.pending_math.take() {
+            Some(pending) => pending,
+            None => return WriteResult::Error(Borrowed("There is no maths waiting to be confirmed"))
+        };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        while self.next < self.question_paper.len(){
-            let node = self.question_paper.nth(self.next).unwrap();
+        if choice == 0 || choice > readings.len() {
+            let message = format!("Say first or second, there are {} ways to read your maths", readings.len());
+            self.pending_math = Some((question, draft, readings));
 
-            self.next += 1;
+            return WriteResult::Error(Cow::from(message));
+        }
 
-            if self.predicate.matches(&node){
-                if self.skip >= 1 {  
-                   
-                    self.skip -= 1;
-                }else{
-                    return Some(node);
-                }
-            }
+        let math = AnswerMath::new(&readings.remove(choice - 1));
+        let message = format!("Added {} to {}", math.spoken, draft.describe(self.label_of(question)));
+
+        self.drafts(draft).entry(question).or_default().append_math(math);
+        self.refresh_answer(question);
+
+        WriteResult::Success(message)
+    }
+
+    // the nodes a note is about, the current node when there are no reads
+    fn note_targets(&mut self, reads: &[Read]) -> Result<Vec<usize>, Cow<'static, str>> {
+        if reads.is_empty(){
+            return Ok(vec![self.prev_index()]);
         }
 
-        None
+        Ok(self.find_nodes(reads)?.iter().map(|node| node.index).collect())
     }
-}
 
+    // the questions a status change is for, the question being read when there are no reads
+    fn question_targets(&mut self, reads: &[Read]) -> Result<Vec<usize>, Cow<'static, str>> {
+        if reads.is_empty(){
+            return self.nth(self.prev_index())
+                .and_then(|node| node.closest(&predicates::QuestionPredicate))
+                .map(|question| vec![question.index()])
+                .ok_or(Borrowed("Go to a question first"));
+        }
 
-impl<'a, P: Predicate> DoubleEndedIterator for Find<'a, P> {
-    fn next_back(&mut self) -> Option<NodeIndex<'a>> {
-        while self.next > 0 {
-            let node = self.question_paper.nth(self.next).unwrap();
+        let questions: Vec<usize> = self.find_nodes(reads)?.iter()
+            .filter(|node| node.data.is_question())
+            .map(|node| node.index)
+            .collect();
 
-            self.next -= 1;
+        match questions.is_empty() {
+            true => Err(Borrowed("Could not find the questions you asked for")),
+            false => Ok(questions)
+        }
+    }
 
-            if self.predicate.matches(&node) {
-                if self.skip >= 1 {
-                    self.skip -= 1;
-                }else{
-                    return Some(node);
+    // positions in the notes of the note a note edit refers to
+    fn find_notes(&mut self, note: &NoteRef) -> Result<Vec<usize>, Cow<'static, str>> {
+        match note {
+            NoteRef::That => match self.last_note.filter(|&position| position < self.notes.len()) {
+                Some(position) => Ok(vec![position]),
+                None => Err(Borrowed("Read or take a note first, then say which note"))
+            },
+            NoteRef::On(ref reads) => {
+                let nodes = self.note_targets(reads)?;
+
+                let found: Vec<usize> = self.notes.iter()
+                    .enumerate()
+                    .filter(|(_, note)| nodes.contains(&note.index))
+                    .map(|(position, _)| position)
+                    .collect();
+
+                match found.is_empty() {
+                    true => Err(Cow::from(format!("There are no notes on {}", self.describe_targets(&nodes)))),
+                    false => Ok(found)
                 }
-               
             }
         }
+    }
 
-        None
+    // how the nodes notes are about are read out, "question 4", "Section B and question 5"
+    fn describe_targets(&self, nodes: &[usize]) -> String {
+        let mut names: Vec<String> = vec![];
+
+        for &index in nodes {
+            let name = self.nodes[index].data.name();
+
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        match names.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            Some((last, _)) => last.clone(),
+            None => "nothing".to_string()
+        }
     }
-}
\ No newline at end of file
+
+    // the printed label of a question for confirmations
+    fn label_of(&self, index: usize) -> &str {
+        match self.nodes[index].data {
+            NodeData::Question(ref question) => &question.label,
+            _ => ""
+        }
+    }
+
+    // the options of a multiple choice question
+    fn choices_of(&self, index: usize) -> Option<&Choices> {
+        match self.nodes[index].data {
+            NodeData::Question(ref question) => question.choices.as_ref(),
+            _ => None
+        }
+    }
+
+    // how chosen options are read back, such as "B, the mitochondria and D, the nucleus"
+    fn describe_choice(choices: &Choices, selected: &[String]) -> String {
+        let chosen: Vec<String> = selected.iter()
+            .filter_map(|label| choices.option(label))
+            .map(|option| format!("{}, {}", option.label, option.text))
+            .collect();
+
+        match chosen.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            Some((last, _)) => last.clone(),
+            None => String::new()
+        }
+    }
+
+    // the answers or the rough work
+    fn drafts(&mut self, draft: Draft) -> &mut BTreeMap<usize, Answer> {
+        match draft {
+            Draft::Answer => &mut self.answers,
+            Draft::RoughWork => &mut self.rough_work
+        }
+    }
+
+    // keep the answered status and section statistics in step with an answer
+    fn refresh_answer(&mut self, index: usize) {
+        let answered = self.answers.get(&index).map(|answer| !answer.is_empty()).unwrap_or(false)
+            || !self.statuses[index].selected.is_empty();
+
+        self.statuses[index].answered_at = if answered { Some(NodeStatus::now()) } else { None };
+        self.refresh_section(index);
+    }
+
+    // confirm a status change on questions, such as "Questions 4, 5 and 6 have been skipped"
+    fn confirm_questions(&self, questions: &[usize], done: &str) -> String {
+        let labels: Vec<&str> = questions.iter().map(|&index| self.label_of(index)).collect();
+
+        match labels.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("Questions {} and {} have been {}", rest.join(", "), last, done),
+            Some((last, _)) => format!("Question {} has been {}", last, done),
+            None => format!("No questions have been {}", done)
+        }
+    }
+
+    // resolve a chain of read intents, each narrowing the search to the node found before it
+    fn find_node(&mut self, reads: &[Read]) -> ReadResult {
+        let mut reads = reads.iter();
+
+        let mut node = match reads.next() {
+            Some(read) => self.resolve_read_intent(read)?,
+            None => return Err(Borrowed("Could not find the specified request"))
+        };
+
+        for read in reads {
+            node = self.resolve_in_scope(node.index, read)?;
+        }
+
+        Ok(node)
+    }
+
+}
+
+impl Reader for QuestionPaper {
+    /// Resolves a read intent
+    fn resolve_read_intent(&mut self, read_intent: &Read) -> ReadResult {
+        match read_intent {
+            Read::Question(ref question) => self.resolve_question(question),
+            Read::QuestionInSection(ref question) => self.resolve_question_in_section(question),
+            Read::QuestionWhere(ref question, ref filters) => self.resolve_filtered_question(question, filters),
+            Read::Section(ref section) => self.resolve_section(section),
+            Read::Passage(ref passage) => self.resolve_passage(passage),
+            Read::Paragraph(ref paragraph) => self.resolve_paragraph(paragraph),
+            Read::Scoped(ref reads) => self.find_node(reads),
+            Read::Page(ref page) => self.resolve_page(page),
+            Read::Figure(ref figure) => self.resolve_figure(figure),
+            Read::Table(ref table) => self.resolve_table(table),
+            Read::TableRow(ref row) => self.resolve_table_row(row),
+            Read::TableColumn(ref column) => self.resolve_table_column(column),
+        }
+    }
+
+    /// Resolve a question
+    fn resolve_question(&mut self, reference: &Reference) -> ReadResult {
+        if let Label(ref label) = reference {
+            return self.resolve_label(label);
+        }
+
+        let predicate = predicates::QuestionPredicate;
+
+        self.resolve_referece(reference, predicate)
+    }
+
+    /// Resolve a question without leaving the section being read
+    fn resolve_question_in_section(&mut self, reference: &Reference) -> ReadResult {
+        let current = match self.nth(self.prev_index()) {
+            Some(current) => current,
+            None => return Err(Borrowed("You are not in a section yet"))
+        };
+
+        let section = match current.closest(&predicates::SectionPredicate) {
+            Some(section) => section.index(),
+            None => return Err(Borrowed("You are not in a section yet"))
+        };
+
+        // a figure or table being read stands in for its question
+        let question = current.closest(&predicates::QuestionPredicate)
+            .map(|question| question.index());
+        let questions = self.questions_in(section);
+
+        if let Label(ref label) = reference {
+            return self.labels.questions(label).iter()
+                .find(|index| questions.contains(index))
+                .map(|&index| self.nodes[index].clone())
+                .ok_or_else(|| Cow::from(format!("There is no question {} in this section", label)));
+        }
+
+        self.resolve_among(&questions, question, reference)
+            .map_err(|_| Borrowed("There are no more questions in this section"))
+    }
+
+    /// Resolve a question that passes every filter
+    fn resolve_filtered_question(&mut self, reference: &Reference, filters: &[Filter]) -> ReadResult {
+        if let Label(_) = reference {
+            return Err(Borrowed("A question label cannot be combined with other conditions"));
+        }
+
+        let predicate = self.question_predicate(filters)?;
+
+        self.resolve_referece(reference, predicate)
+            .map_err(|_| Borrowed("There is no question like that"))
+    }
+
+    /// Resolve a section
+    fn resolve_section(&mut self, reference: &Reference) -> ReadResult {
+        let predicate = predicates::SectionPredicate;
+
+        if let Label(ref name) = reference {
+            if let Some(section) = self.labels.section(name) {
+                return Ok(self.nodes[section].clone());
+            }
+
+            // "section 2" counts sections when they are named by letter
+            return match name.parse::<usize>().ok().and_then(|ordinal| self.labels.nth_section(ordinal)) {
+                Some(section) => Ok(self.nodes[section].clone()),
+                None => Err(Cow::from(format!("There is no section {}", name)))
+            };
+        }
+
+        self.resolve_referece(reference, predicate)
+    }
+
+    /// Resolve a passage
+    fn resolve_passage(&mut self, reference: &Reference) -> ReadResult {
+        let predicate = predicates::PassagePredicate;
+
+        self.resolve_referece(reference, predicate)
+    }
+
+    /// Resolve a paragraph of the passage being read
+    fn resolve_paragraph(&mut self, reference: &Reference) -> ReadResult {
+        let current = self.nth(self.prev_index());
+
+        let passage = match current.and_then(|node| node.closest(&predicates::PassagePredicate)) {
+            Some(passage) => passage.index(),
+            None => return Err(Borrowed("You are not reading a passage"))
+        };
+
+        let paragraph = current.filter(|node| node.data().is_paragraph())
+            .map(|node| node.index());
+
+        self.resolve_child(passage, paragraph, reference)
+    }
+
+    /// Resolve a page by its printed number, or relative to the page currently being read
+    fn resolve_page(&mut self, reference: &Reference) -> ReadResult {
+        let page_number = match reference {
+            Start(number) => *number,
+            Current(offset) => {
+                let current = self.nth(self.prev_index())
+                    .and_then(|node| node.data().page_number());
+
+                match current {
+                    Some(current) => current as i32 + offset,
+                    None => return Err(Borrowed("You are not on any page yet"))
+                }
+            },
+            _ => return self.resolve_referece(reference, predicates::PagePredicate)
+        };
+
+        if page_number < 1 {
+            return Err(Borrowed("There is no such page"));
+        }
+
+        let predicate = predicates::PageNumberPredicate(page_number as u32);
+
+        match self.find(predicate, 0, 1).next() {
+            Some(page) => Ok(page.raw().clone()),
+            None => Err(Cow::from(format!("There is no page {}", page_number)))
+        }
+    }
+
+    /// Resolve a figure or an image
+    fn resolve_figure(&mut self, reference: &Reference) -> ReadResult {
+        let predicate = predicates::FigurePredicate;
+
+        self.resolve_referece(reference, predicate)
+    }
+
+    /// Resolve a table
+    fn resolve_table(&mut self, reference: &Reference) -> ReadResult {
+        let predicate = predicates::TablePredicate;
+
+        self.resolve_referece(reference, predicate)
+    }
+
+    /// Resolve a row in the table being read
+    fn resolve_table_row(&mut self, reference: &Reference) -> ReadResult {
+        let table = match self.current_table() {
+            Some(table) => table,
+            None => return Err(Borrowed("You are not reading a table"))
+        };
+
+        let current = self.current_row();
+
+        self.resolve_child(table, current, reference)
+    }
+
+    /// Resolve a cell in the row being read
+    fn resolve_table_column(&mut self, reference: &Reference) -> ReadResult {
+        let row = match self.current_row() {
+            Some(row) => row,
+            None => return Err(Borrowed("Choose a table row first"))
+        };
+
+        let current = match self.nth(self.prev_index()) {
+            Some(ref node) if node.raw().parent == Some(row) => Some(node.index()),
+            _ => None
+        };
+
+        self.resolve_child(row, current, reference)
+    }
+
+    /// Resolve from a reference
+    fn resolve_referece<P: Predicate>(&mut self, reference: &Reference, predicate: P) -> ReadResult {
+
+        let (prev, skip) = match reference {
+            Start(skip) => (0, skip.unsigned_abs() as usize),
+            Current(skip) => {
+                let prev = self.prev_index();
+
+                // the current node only counts towards the offset when it is of the kind searched for
+                let on_match = self.nth(prev).map(|node| predicate.matches(&node)).unwrap_or(false);
+                let skip = skip.unsigned_abs() as usize + if on_match { 1 } else { 0 };
+
+                (prev, skip.max(1))
+            },
+            End(skip) => (self.last_index(), skip.unsigned_abs() as usize),
+            Label(_) => return Err(Borrowed("Only questions can be found by their label")),
+            Range(_, _) => return Err(Borrowed("A range cannot be read as a single item"))
+        };
+        
+
+        self.resolve(predicate, prev, skip, reference)
+    }
+
+    fn resolve<P: Predicate>(&mut self, predicate: P, prev: usize, skip: usize, reference: &Reference) -> ReadResult {
+        let finder = self.find(predicate, prev, skip);
+
+
+        if reference.is_forward(){
+            self.find_next(finder)
+        }else{
+            self.find_back(finder)
+        }
+    }
+
+    /// Do a foward find
+    fn find_next<P: Predicate>(&self, mut finder: Find<P>) -> ReadResult {
+        if let Some(node) = finder.next(){
+            Ok(node.raw().clone())
+        }else{
+            Err(Borrowed("Could not find a next node"))
+        }
+    }
+
+    /// Do a reverse find
+    fn find_back<P: Predicate>(&self, mut finder: Find<P>) -> ReadResult {
+        if let Some(node) = finder.next_back(){
+            Ok(node.raw().clone())
+        }else{
+            Err(Borrowed("Could not resolve a previous node"))
+        }
+    }
+
+}
+
+impl AnswerReader for QuestionPaper {
+    /// Read back an answer, or part of the current one
+    fn read_answer(&mut self, read: &AnswerRead) -> Result<String, Cow<'static, str>> {
+        let reads = match read {
+            AnswerRead::Whole(ref reads) | AnswerRead::RoughWork(ref reads) | AnswerRead::Choice(ref reads) => reads.clone(),
+            _ => vec![]
+        };
+
+        let draft = match read {
+            AnswerRead::RoughWork(_) => Draft::RoughWork,
+            AnswerRead::Choice(_) => Draft::Answer,
+            _ => self.drafting
+        };
+
+        let question = self.answer_target(&reads)?;
+        let label = self.label_of(question).to_string();
+
+        // the answer to a multiple choice question is the options chosen
+        if let (Some(choices), Draft::Answer) = (self.choices_of(question), draft) {
+            match read {
+                AnswerRead::Whole(_) | AnswerRead::Choice(_) => {
+                    let selected = &self.statuses[question].selected;
+
+                    return match selected.is_empty() {
+                        true => Err(Cow::from(format!("You have not chosen an answer for question {}", label))),
+                        false => Ok(format!("For question {} you chose {}", label, Self::describe_choice(choices, selected)))
+                    };
+                },
+                _ => ()
+            }
+        }
+
+        if let AnswerRead::Choice(_) = read {
+            return Err(Cow::from(format!("Question {} is not a multiple choice question", label)));
+        }
+
+        let answer = match self.drafts(draft).get_mut(&question) {
+            Some(answer) if !answer.is_empty() => answer,
+            _ => return Err(Cow::from(match draft {
+                Draft::Answer => format!("You have not answered question {}", label),
+                Draft::RoughWork => format!("You have no rough work for question {}", label)
+            }))
+        };
+
+        match read {
+            // rough work is announced as such so it is not taken for the answer
+            AnswerRead::Whole(_) | AnswerRead::RoughWork(_) => match draft {
+                Draft::Answer => Ok(format!("Your answer to question {}: {}", label, answer.spoken())),
+                Draft::RoughWork => Ok(format!("Rough work for question {}, not marked: {}", label, answer.spoken()))
+            },
+            AnswerRead::Piece(unit, position) => answer.read(*unit, *position),
+            AnswerRead::Spell(ref word) => {
+                let word = answer.word(word.as_ref().map(|word| word.as_str()))?;
+
+                Ok(format!("{} is spelt {}", word, spell(&word)))
+            },
+            AnswerRead::Choice(_) => Err(Borrowed("Could not read your choice"))
+        }
+    }
+}
+
+impl NoteReader for QuestionPaper {
+    /// Read the notes on the nodes found, or every note
+    fn read_notes(&mut self, read: &NoteRead) -> Result<String, Cow<'static, str>> {
+        let found: Vec<usize> = match read {
+            NoteRead::On(ref reads) => self.find_notes(&NoteRef::On(reads.clone()))?,
+            NoteRead::All => (0..self.notes.len()).collect()
+        };
+
+        if found.is_empty(){
+            return Err(Borrowed("You have not taken any notes"));
+        }
+
+        self.last_note = found.last().cloned();
+
+        let notes: Vec<String> = found.iter()
+            .map(|&position| &self.notes[position])
+            .map(|note| format!("On {}: {}", self.nodes[note.index].data.name(), note.note.trim_end_matches('.')))
+            .collect();
+
+        Ok(format!("{} {}. {}.", found.len(), plural(found.len() as u32, "note"), notes.join(". ")))
+    }
+}
+
+impl Writer for QuestionPaper {
+    /// Resolve a write intent
+    fn resolve_write_intent(&mut self, write_intent: &Write) ->  WriteResult{
+        // the work before the write, kept when the write changes it so that it can be undone
+        let before = if write_intent.changes_work() { Some(self.snapshot()) } else { None };
+
+        let result = match write_intent {
+            Write::Mark(ref read_intents) => self.mark_for_review(read_intents),
+            Write::Skip(ref read_intents) => self.skip(read_intents),
+            Write::Unmark(ref read_intents) => self.unmark(read_intents),
+            Write::Unskip(ref read_intents) => self.unskip(read_intents),
+            Write::Note(ref read_intents, note) => self.note(read_intents, note.to_string()),
+            Write::EditNote(ref note, ref text) => self.edit_note(note, text.to_string()),
+            Write::DeleteNote(ref note) => self.delete_note(note),
+            Write::Bulk(action, ref scope, ref filters) => self.bulk_write(*action, scope, filters),
+            // starting an answer leaves the rough work, other edits go to what is being written
+            Write::Answer(ref read_intents, AnswerEdit::Start) => self.answer(Draft::Answer, read_intents, &AnswerEdit::Start),
+            // a question named in the request gets its answer, dictation that follows leaves the rough work too
+            Write::Answer(ref read_intents, ref edit) if !read_intents.is_empty() => {
+                let result = self.answer(Draft::Answer, read_intents, edit);
+
+                if let WriteResult::Success(_) = result {
+                    self.drafting = Draft::Answer;
+                }
+
+                result
+            },
+            Write::Answer(ref read_intents, ref edit) => self.answer(self.drafting, read_intents, edit),
+            Write::RoughWork(ref read_intents, ref edit) => self.answer(Draft::RoughWork, read_intents, edit),
+            Write::Choose(ref read_intents, ref edit) => self.choose(read_intents, edit),
+            Write::Undo => self.undo(),
+            Write::Redo => self.redo()
+        };
+
+        if let (WriteResult::Success(ref change), Some(before)) = (&result, before) {
+            if before != self.snapshot() {
+                self.history.record(change.clone(), before);
+            }
+        }
+
+        result
+    }
+
+
+    // process a read intent and mark its questions for review
+    fn mark_for_review(&mut self, reads: &[Read]) -> WriteResult {
+        let questions = match self.question_targets(reads) {
+            Ok(questions) => questions,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        for &index in &questions {
+            if let NodeData::Question(ref mut question) = self.nodes[index].data {
+                question.marked = true;
+            }
+
+            self.statuses[index].marked_at = Some(NodeStatus::now());
+            self.refresh_section(index);
+        }
+
+        WriteResult::Success(self.confirm_questions(&questions, "marked for review"))
+    }
+
+    fn skip(&mut self, reads: &[Read]) -> WriteResult {
+        let questions = match self.question_targets(reads) {
+            Ok(questions) => questions,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        for &index in &questions {
+            self.statuses[index].skipped_at = Some(NodeStatus::now());
+            self.refresh_section(index);
+        }
+
+        WriteResult::Success(self.confirm_questions(&questions, "skipped"))
+    }
+
+    fn unmark(&mut self, reads: &[Read]) -> WriteResult {
+        let questions = match self.question_targets(reads) {
+            Ok(questions) => questions,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let marked: Vec<usize> = questions.iter().cloned().filter(|&index| self.statuses[index].is_marked()).collect();
+
+        if marked.is_empty() {
+            return WriteResult::Error(Cow::from(format!(
+                "Nothing was unmarked, {} {} not marked for review", self.describe_targets(&questions), if questions.len() == 1 { "is" } else { "are" }
+            )));
+        }
+
+        for &index in &marked {
+            if let NodeData::Question(ref mut question) = self.nodes[index].data {
+                question.marked = false;
+            }
+
+            self.statuses[index].marked_at = None;
+            self.refresh_section(index);
+        }
+
+        WriteResult::Success(format!("Unmarked {}", self.describe_targets(&marked)))
+    }
+
+    fn unskip(&mut self, reads: &[Read]) -> WriteResult {
+        let questions = match self.question_targets(reads) {
+            Ok(questions) => questions,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let skipped: Vec<usize> = questions.iter().cloned().filter(|&index| self.statuses[index].is_skipped()).collect();
+
+        if skipped.is_empty() {
+            return WriteResult::Error(Cow::from(format!(
+                "Nothing was unskipped, {} {} not skipped", self.describe_targets(&questions), if questions.len() == 1 { "is" } else { "are" }
+            )));
+        }
+
+        for &index in &skipped {
+            self.statuses[index].skipped_at = None;
+            self.refresh_section(index);
+        }
+
+        WriteResult::Success(format!("Unskipped {}", self.describe_targets(&skipped)))
+    }
+
+    /// Take a note on this node
+    fn note(&mut self, reads: &[Read], note: String) -> WriteResult {
+        let nodes = match self.note_targets(reads) {
+            Ok(nodes) if !nodes.is_empty() => nodes,
+            _ => return WriteResult::Error(Borrowed("Could not take a note as requested"))
+        };
+
+        // notes are dictated like answers
+        let note = dictation::format(&note, self.metadata.locale, true);
+
+        if note.is_empty(){
+            return WriteResult::Error(Borrowed("There was nothing to note"));
+        }
+
+        for &index in &nodes {
+            self.notes.push(Note {
+                note: note.clone(),
+                index
+            });
+        }
+
+        self.last_note = Some(self.notes.len() - 1);
+
+        WriteResult::Success(format!("Took a note on {}", self.describe_targets(&nodes)))
+    }
+
+    fn edit_note(&mut self, note: &NoteRef, text: String) -> WriteResult {
+        let found = match self.find_notes(note) {
+            Ok(found) => found,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let text = dictation::format(&text, self.metadata.locale, true);
+
+        if text.is_empty(){
+            return WriteResult::Error(Borrowed("Say what the note should say"));
+        }
+
+        // the latest note when a node has several
+        let position = found[found.len() - 1];
+
+        self.notes[position].note = text;
+        self.last_note = Some(position);
+
+        WriteResult::Success(format!("Changed your note on {}", self.nodes[self.notes[position].index].data.name()))
+    }
+
+    fn delete_note(&mut self, note: &NoteRef) -> WriteResult {
+        let found = match self.find_notes(note) {
+            Ok(found) => found,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let nodes: Vec<usize> = found.iter().map(|&position| self.notes[position].index).collect();
+        let described = self.describe_targets(&nodes);
+
+        // remove from the back so earlier positions stay valid
+        for &position in found.iter().rev() {
+            self.notes.remove(position);
+        }
+
+        self.last_note = None;
+
+        match found.len() {
+            1 => WriteResult::Success(format!("Deleted your note on {}", described)),
+            n => WriteResult::Success(format!("Deleted {} notes on {}", n, described))
+        }
+    }
+
+    fn undo(&mut self) -> WriteResult {
+        let current = self.snapshot();
+
+        match self.history.undo(current) {
+            Some((change, before)) => {
+                self.restore(before);
+
+                WriteResult::Success(format!("Undone: {}", change))
+            },
+            None => WriteResult::Error(Borrowed("There is nothing to undo"))
+        }
+    }
+
+    fn redo(&mut self) -> WriteResult {
+        let current = self.snapshot();
+
+        match self.history.redo(current) {
+            Some((change, after)) => {
+                self.restore(after);
+
+                WriteResult::Success(format!("Redone: {}", change))
+            },
+            None => WriteResult::Error(Borrowed("There is nothing to redo"))
+        }
+    }
+
+    /// Apply a write to every matching question in a scope
+    fn bulk_write(&mut self, action: BulkAction, scope: &[Read], filters: &[Filter]) -> WriteResult {
+        let scope = if scope.is_empty() {
+            None
+        }else{
+            match self.find_node(scope) {
+                Ok(node) => Some(node.index),
+                Err(e) => return WriteResult::Error(e)
+            }
+        };
+
+        let predicate = match self.question_predicate(filters) {
+            Ok(predicate) => predicate,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let matched: Vec<usize> = (0..self.len())
+            .filter_map(|index| self.nth(index))
+            .filter(|node| predicate.matches(node))
+            .filter(|node| scope.map(|scope| predicates::WithinPredicate(scope).matches(node)).unwrap_or(true))
+            .map(|node| node.index())
+            .collect();
+
+        for &index in &matched {
+            match action {
+                BulkAction::Mark => {
+                    if let NodeData::Question(ref mut question) = self.nodes[index].data {
+                        question.marked = true;
+                    }
+
+                    self.statuses[index].marked_at = Some(NodeStatus::now());
+                },
+                BulkAction::Skip => self.statuses[index].skipped_at = Some(NodeStatus::now()),
+                BulkAction::ClearMark => {
+                    if let NodeData::Question(ref mut question) = self.nodes[index].data {
+                        question.marked = false;
+                    }
+
+                    self.statuses[index].marked_at = None;
+                },
+                BulkAction::ClearSkip => self.statuses[index].skipped_at = None
+            }
+
+            self.refresh_section(index);
+        }
+
+        WriteResult::Success(action.describe(matched.len() as u32))
+    }
+
+    /// Change the answer to a question
+    fn answer(&mut self, draft: Draft, reads: &[Read], edit: &AnswerEdit) -> WriteResult {
+        // the maths goes to the question it was dictated for
+        if let AnswerEdit::ConfirmMath(choice) = edit {
+            return self.confirm_math(*choice);
+        }
+
+        let question = match self.answer_target(reads) {
+            Ok(question) => question,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let label = self.label_of(question).to_string();
+        let described = draft.describe(&label);
+        // dictation is formatted in the language of the paper
+        let locale = self.metadata.locale;
+
+        let message = match edit {
+            AnswerEdit::Start => {
+                self.answering = Some(question);
+                self.drafting = draft;
+                self.update_previous(question);
+                self.statuses[question].visited_at = Some(NodeStatus::now());
+
+                let started = match draft {
+                    Draft::Answer => format!("Answering question {}", label),
+                    Draft::RoughWork => format!("Writing rough work for question {}, it will not be marked", label)
+                };
+
+                match self.drafts(draft).get(&question) {
+                    Some(answer) => format!("{}. So far it has {} words", started, answer.word_count()),
+                    None => format!("{}. Start dictating", started)
+                }
+            },
+            AnswerEdit::Append(ref text) => {
+                if text.trim().is_empty() {
+                    return WriteResult::Error(Cow::from(format!("There was nothing to add to {}", described)));
+                }
+
+                let answer = self.drafts(draft).entry(question).or_default();
+                let text = dictation::format(text, locale, answer.starts_sentence(answer.text.len()));
+
+                answer.append(&text);
+                format!("Added to {}", described)
+            },
+            AnswerEdit::Replace(ref text) => {
+                let text = dictation::format(text, locale, true);
+
+                self.drafts(draft).entry(question).or_default().replace(&text);
+                format!("Replaced {}", described)
+            },
+            AnswerEdit::Clear => {
+                match self.drafts(draft).get_mut(&question) {
+                    Some(answer) => answer.clear(),
+                    None => return WriteResult::Error(Cow::from(format!("There is nothing in {}", described)))
+                }
+
+                format!("Cleared {}", described)
+            },
+            AnswerEdit::Stop => {
+                self.answering = None;
+                self.drafting = Draft::Answer;
+
+                match draft {
+                    Draft::Answer => format!("Stopped answering question {}", label),
+                    Draft::RoughWork => format!("Stopped rough work for question {}", label)
+                }
+            },
+            AnswerEdit::Math(ref spoken) => {
+                let mut readings = match SpokenParser::parse(spoken) {
+                    Ok(readings) => readings,
+                    Err(e) => return WriteResult::Error(e)
+                };
+
+                if readings.len() > 1 {
+                    let choices: Vec<String> = readings.iter()
+                        .zip(["first", "second"].iter())
+                        .map(|(reading, position)| format!("{}, {}", position, reading.speak()))
+                        .collect();
+
+                    self.pending_math = Some((question, draft, readings));
+
+                    return WriteResult::Confirm(format!("Did you mean {}? Say first or second", choices.join("; or ")));
+                }
+
+                let math = AnswerMath::new(&readings.remove(0));
+                let message = format!("Added {} to {}", math.spoken, described);
+
+                self.drafts(draft).entry(question).or_default().append_math(math);
+                message
+            },
+            edit => {
+                let answer = self.drafts(draft).entry(question).or_default();
+
+                let changed = match edit {
+                    AnswerEdit::Insert(ref text) => {
+                        let text = dictation::format(text, locale, answer.starts_sentence(answer.cursor));
+
+                        answer.insert(&text)
+                    },
+                    AnswerEdit::InsertParagraph => answer.insert_paragraph(),
+                    AnswerEdit::Delete(unit, position) => answer.delete(*unit, *position),
+                    AnswerEdit::ReplacePhrase(ref from, ref to) => answer.replace_phrase(from, to),
+                    AnswerEdit::MoveCursor(unit, position) => answer.move_cursor(*unit, *position),
+                    // the remaining edits are handled above
+                    _ => Err(Borrowed("Could not change your answer"))
+                };
+
+                match changed {
+                    Ok(message) => message,
+                    Err(e) => return WriteResult::Error(e)
+                }
+            }
+        };
+
+        // starting, stopping and moving the cursor leave the answer as it was, rough work is not an answer
+        match (draft, edit) {
+            (Draft::RoughWork, _) | (_, AnswerEdit::Start) | (_, AnswerEdit::Stop) | (_, AnswerEdit::MoveCursor(_, _)) => (),
+            _ => self.refresh_answer(question)
+        }
+
+        WriteResult::Success(message)
+    }
+
+    /// Choose, change or remove options, checking them against the options of the question
+    fn choose(&mut self, reads: &[Read], edit: &ChoiceEdit) -> WriteResult {
+        let question = match self.answer_target(reads) {
+            Ok(question) => question,
+            Err(e) => return WriteResult::Error(e)
+        };
+
+        let label = self.label_of(question).to_string();
+
+        let choices = match self.choices_of(question) {
+            Some(choices) => choices.clone(),
+            None => return WriteResult::Error(Cow::from(format!("Question {} is not a multiple choice question", label)))
+        };
+
+        if edit.labels().is_empty() {
+            return WriteResult::Error(Cow::from(format!("Say which option you want, {}", choices.labels())));
+        }
+
+        // the options as printed, refusing labels the question does not have
+        let mut chosen: Vec<String> = vec![];
+
+        for spoken in edit.labels() {
+            match choices.option(spoken) {
+                Some(option) if !chosen.contains(&option.label) => chosen.push(option.label.clone()),
+                Some(_) => (),
+                None => return WriteResult::Error(Cow::from(format!(
+                    "Question {} has no option {}. Choose {}", label, spoken.trim(), choices.labels()
+                )))
+            }
+        }
+
+        if !choices.multiple && chosen.len() > 1 {
+            if let ChoiceEdit::Select(_) | ChoiceEdit::Change(_) = edit {
+                return WriteResult::Error(Cow::from(format!("Question {} takes only one answer", label)));
+            }
+        }
+
+        let selected = &mut self.statuses[question].selected;
+
+        match edit {
+            ChoiceEdit::Select(_) if choices.multiple => {
+                for option in chosen {
+                    if !selected.contains(&option) {
+                        selected.push(option);
+                    }
+                }
+            },
+            ChoiceEdit::Select(_) | ChoiceEdit::Change(_) => *selected = chosen,
+            ChoiceEdit::Deselect(_) => {
+                if let Some(missing) = chosen.iter().find(|option| !selected.contains(option)) {
+                    return WriteResult::Error(Cow::from(format!("You have not chosen option {} for question {}", missing, label)));
+                }
+
+                selected.retain(|option| !chosen.contains(option));
+            }
+        }
+
+        let message = match self.statuses[question].selected.is_empty() {
+            true => format!("You have no answer for question {}", label),
+            false => format!("For question {} you chose {}", label, Self::describe_choice(&choices, &self.statuses[question].selected))
+        };
+
+        self.update_previous(question);
+        self.statuses[question].visited_at = Some(NodeStatus::now());
+        self.refresh_answer(question);
+
+        WriteResult::Success(message)
+    }
+}
+
+
+pub struct Find<'a, P:Predicate> {
+    predicate: P,
+    next: usize,
+    question_paper: &'a QuestionPaper,
+    skip: usize
+}
+
+impl <'a, P: Predicate> Iterator for Find<'a, P> {
+    type Item = NodeIndex<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.question_paper.len(){
+            let node = self.question_paper.nth(self.next).unwrap();
+
+            self.next += 1;
+
+            if self.predicate.matches(&node){
+                if self.skip >= 1 {  
+                   
+                    self.skip -= 1;
+                }else{
+                    return Some(node);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+
+impl<'a, P: Predicate> DoubleEndedIterator for Find<'a, P> {
+    fn next_back(&mut self) -> Option<NodeIndex<'a>> {
+        while self.next > 0 {
+            let node = self.question_paper.nth(self.next).unwrap();
+
+            self.next -= 1;
+
+            if self.predicate.matches(&node) {
+                if self.skip >= 1 {
+                    self.skip -= 1;
+                }else{
+                    return Some(node);
+                }
+               
+            }
+        }
+
+        None
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Tokenizer, Sink};
+    use std::sync::mpsc;
+
+    const PAPER: &str = r#"<?xml version="1.0"?>
+        <root>
+        <meta_data><title>Biology</title></meta_data>
+        <SECTION_A>
+        <section_number>Section A</section_number>
+        <page_1>
+        <item><question_number>1</question_number><question>Where is energy released?</question>
+        <options><option>the nucleus</option><option>the mitochondria</option></options></item>
+        <item><question_number>2</question_number><question>Name a gas.</question></item>
+        <item><question_number>3</question_number><question>Explain.</question></item>
+        </page_1>
+        </SECTION_A>
+        <SECTION_B>
+        <section_number>Section B</section_number>
+        <page_2>
+        <item><question_number>4</question_number><question>Describe a cell.</question></item>
+        </page_2>
+        </SECTION_B>
+        </root>"#;
+
+    fn paper() -> QuestionPaper {
+        build(PAPER)
+    }
+
+    fn build(paper: &str) -> QuestionPaper {
+        let (tx, rx) = mpsc::channel();
+        Tokenizer::tokenize(vec![paper.to_string()], Sink::new(tx));
+
+        let mut builder = QPaperBuilder::new();
+
+        for tag in rx {
+            builder.process_tag(tag);
+        }
+
+        builder.end().expect("the paper should build")
+    }
+
+    fn question(label: &str) -> Vec<Read> {
+        vec![Read::Question(Reference::Label(label.to_string()))]
+    }
+
+    fn index_of(paper: &QuestionPaper, label: &str) -> usize {
+        paper.labels.questions(label)[0]
+    }
+
+    fn meta(paper: &mut QuestionPaper, meta: MetaIntent) -> String {
+        match paper.resolve_intent(Intent::Meta(meta)) {
+            IntentResult::Meta(message) => message,
+            result => panic!("expected an answer about the paper: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn metadata_is_read_out(){
+        let mut paper = paper();
+
+        assert_eq!(meta(&mut paper, MetaIntent::Paper), "This is Biology");
+        assert_eq!(meta(&mut paper, MetaIntent::Duration), "The paper does not say how long the exam lasts");
+        assert_eq!(meta(&mut paper, MetaIntent::TotalMarks), "The paper does not say how many marks are available");
+    }
+
+    #[test]
+    fn undo_keeps_visits(){
+        let mut paper = paper();
+
+        paper.resolve_intent(Intent::WriteIntent(Write::Mark(question("1"))));
+        paper.resolve_intent(Intent::ReadIntent(Read::Question(Reference::Label("2".to_string()))));
+        paper.resolve_intent(Intent::WriteIntent(Write::Undo));
+
+        let second = index_of(&paper, "2");
+
+        assert!(paper.status(second).unwrap().is_visited());
+        assert!(!paper.status(index_of(&paper, "1")).unwrap().is_marked());
+        assert_eq!(paper.num_marked(), 0);
+    }
+
+    #[test]
+    fn undo_and_redo_say_what_changed(){
+        let mut paper = paper();
+
+        paper.resolve_intent(Intent::WriteIntent(Write::Skip(question("3"))));
+
+        match paper.resolve_write_intent(&Write::Undo) {
+            WriteResult::Success(message) => assert_eq!(message, "Undone: Question 3 has been skipped"),
+            result => panic!("undo failed: {:?}", result)
+        }
+
+        assert_eq!(paper.num_skipped(), 0);
+
+        match paper.resolve_write_intent(&Write::Redo) {
+            WriteResult::Success(message) => assert_eq!(message, "Redone: Question 3 has been skipped"),
+            result => panic!("redo failed: {:?}", result)
+        }
+
+        assert_eq!(paper.num_skipped(), 1);
+        assert_eq!(paper.resolve_write_intent(&Write::Redo), WriteResult::Error(Borrowed("There is nothing to redo")));
+    }
+
+    fn section(paper: &mut QuestionPaper, name: &str) -> Result<String, Cow<'static, str>> {
+        paper.resolve_section(&Reference::Label(name.to_string())).map(|node| node.data.name())
+    }
+
+    #[test]
+    fn sections_by_name_and_ordinal(){
+        let mut paper = paper();
+
+        assert_eq!(section(&mut paper, "B"), Ok("Section B".to_string()));
+        assert_eq!(section(&mut paper, "section b"), Ok("Section B".to_string()));
+        assert_eq!(section(&mut paper, "1"), Ok("Section A".to_string()));
+        assert_eq!(section(&mut paper, "2"), Ok("Section B".to_string()));
+        assert_eq!(section(&mut paper, "3"), Err(Cow::from("There is no section 3")));
+    }
+
+    #[test]
+    fn section_zero_and_negative(){
+        let mut paper = paper();
+
+        assert_eq!(section(&mut paper, "0"), Err(Cow::from("There is no section 0")));
+        assert_eq!(section(&mut paper, "-1"), Err(Cow::from("There is no section -1")));
+    }
+
+    #[test]
+    fn named_question_is_answered_from_rough_work(){
+        let mut paper = paper();
+
+        paper.resolve_write_intent(&Write::RoughWork(question("3"), AnswerEdit::Start));
+        paper.resolve_write_intent(&Write::Answer(vec![], AnswerEdit::Append("two plus two".to_string())));
+        paper.resolve_write_intent(&Write::Answer(question("2"), AnswerEdit::Append("oxygen".to_string())));
+
+        let second = index_of(&paper, "2");
+        let third = index_of(&paper, "3");
+
+        assert_eq!(paper.answer(second).map(|answer| answer.text.as_str()), Some("Oxygen"));
+        assert!(paper.rough_work(second).is_none());
+
+        // dictation carries on into the answer of the question being worked on
+        paper.resolve_write_intent(&Write::Answer(vec![], AnswerEdit::Append("four".to_string())));
+
+        assert_eq!(paper.answer(third).map(|answer| answer.text.as_str()), Some("Four"));
+        assert_eq!(paper.rough_work(third).map(|answer| answer.text.as_str()), Some("Two plus two"));
+    }
+
+    fn read(paper: &mut QuestionPaper, read: Read) -> Result<String, Cow<'static, str>> {
+        match paper.resolve_intent(Intent::ReadIntent(read)) {
+            IntentResult::Read(result) => result.map(|data| data.name()),
+            result => panic!("expected a read: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn questions_by_label(){
+        let mut paper = paper();
+
+        assert_eq!(read(&mut paper, Read::Question(Reference::Label("4".to_string()))), Ok("question 4".to_string()));
+        assert_eq!(read(&mut paper, Read::Question(Reference::Label("9".to_string()))), Err(Cow::from("There is no question 9")));
+        assert_eq!(read(&mut paper, Read::Question(Reference::Label("Question 2".to_string()))), Ok("question 2".to_string()));
+        assert_eq!(
+            read(&mut paper, Read::Section(Reference::Label("4".to_string()))),
+            Err(Cow::from("There is no section 4"))
+        );
+    }
+
+    #[test]
+    fn repeated_labels_prefer_the_current_section(){
+        let mut paper = build(r#"<?xml version="1.0"?>
+            <root>
+            <meta_data><numbering>per section</numbering></meta_data>
+            <SECTION_A>
+            <section_number>Section A</section_number>
+            <page_1><item><question>Add.</question></item><item><question>Subtract.</question></item></page_1>
+            </SECTION_A>
+            <SECTION_B>
+            <section_number>Section B</section_number>
+            <page_2><item><question>Multiply.</question></item></page_2>
+            </SECTION_B>
+            </root>"#);
+
+        let in_a = paper.labels.questions("1")[0];
+        let in_b = paper.labels.questions("1")[1];
+
+        read(&mut paper, Read::Question(Reference::Label("1".to_string()))).unwrap();
+        assert_eq!(paper.prev_index(), in_a);
+
+        read(&mut paper, Read::Section(Reference::Label("B".to_string()))).unwrap();
+        read(&mut paper, Read::Question(Reference::Label("1".to_string()))).unwrap();
+        assert_eq!(paper.prev_index(), in_b);
+    }
+
+    #[test]
+    fn pages_by_number(){
+        let mut paper = paper();
+
+        assert_eq!(read(&mut paper, Read::Page(Reference::Start(2))), Ok("page 2".to_string()));
+        assert_eq!(read(&mut paper, Read::Page(Reference::Start(0))), Err(Cow::from("There is no such page")));
+        assert_eq!(read(&mut paper, Read::Page(Reference::Start(5))), Err(Cow::from("There is no page 5")));
+
+        read(&mut paper, Read::Question(Reference::Label("2".to_string()))).unwrap();
+        assert_eq!(read(&mut paper, Read::Page(Reference::Current(0))), Ok("page 1".to_string()));
+    }
+
+    const TABLE: &str = r#"<?xml version="1.0"?>
+        <root>
+        <SECTION_A>
+        <section_number>Section A</section_number>
+        <page_1>
+        <item><question_number>1</question_number><question>Which day was wettest?</question>
+        <table><tr><th>Day</th><th>Rain</th></tr><tr><td>Monday</td><td>4</td></tr><tr><td>Tuesday</td><td>7</td></tr></table>
+        <figure><img src="gauge.png" alt="A rain gauge"/></figure>
+        </item>
+        </page_1>
+        </SECTION_A>
+        </root>"#;
+
+    fn read_list(paper: &mut QuestionPaper, read: Read) -> Result<Vec<String>, Cow<'static, str>> {
+        match paper.resolve_intent(Intent::ReadIntent(read)) {
+            IntentResult::ReadList(result) => result.map(|nodes| nodes.iter().map(|data| data.name()).collect()),
+            result => panic!("expected a list read: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn tables_and_figures(){
+        let mut paper = build(TABLE);
+
+        assert_eq!(read(&mut paper, Read::TableColumn(Reference::Start(1))), Err(Cow::from("Choose a table row first")));
+        assert_eq!(read(&mut paper, Read::Figure(Reference::Start(1))), Ok("the figure on page 1".to_string()));
+        assert_eq!(read(&mut paper, Read::Table(Reference::Start(1))), Ok("the table on page 1".to_string()));
+
+        // a column is read down every row until a row is chosen
+        assert_eq!(
+            read_list(&mut paper, Read::TableColumn(Reference::Start(2))),
+            Ok(vec!["row 1 column 2".to_string(), "row 2 column 2".to_string()])
+        );
+
+        assert_eq!(read(&mut paper, Read::TableRow(Reference::Start(1))), Ok("row 1".to_string()));
+        assert_eq!(read(&mut paper, Read::TableColumn(Reference::Start(2))), Ok("row 1 column 2".to_string()));
+        assert_eq!(read(&mut paper, Read::TableRow(Reference::Current(1))), Ok("row 2".to_string()));
+        assert_eq!(read(&mut paper, Read::TableRow(Reference::End(2))), Ok("row 1".to_string()));
+    }
+
+    #[test]
+    fn column_read_from_the_question_of_a_table(){
+        let mut paper = build(TABLE);
+
+        read(&mut paper, Read::Question(Reference::Label("1".to_string()))).unwrap();
+
+        assert_eq!(read_list(&mut paper, Read::TableColumn(Reference::Start(3))), Err(Cow::from("There is nothing at that position")));
+        assert_eq!(
+            read_list(&mut paper, Read::TableColumn(Reference::Start(1))),
+            Ok(vec!["row 1 column 1".to_string(), "row 2 column 1".to_string()])
+        );
+
+        // the candidate carries on from the last row
+        assert_eq!(read(&mut paper, Read::TableColumn(Reference::Start(2))), Ok("row 2 column 2".to_string()));
+    }
+
+    #[test]
+    fn section_progress_follows_marks_skips_and_answers(){
+        let mut paper = paper();
+
+        assert_eq!(meta(&mut paper, MetaIntent::Progress), "You have not started a section yet");
+
+        paper.resolve_write_intent(&Write::Mark(question("1")));
+        paper.resolve_write_intent(&Write::Skip(question("2")));
+        read(&mut paper, Read::Question(Reference::Label("3".to_string()))).unwrap();
+
+        assert_eq!(meta(&mut paper, MetaIntent::Progress), "Section A: 3 questions, 0 answered, 1 marked, 1 skipped, 2 remaining");
+
+        // answering a skipped question no longer leaves it outstanding
+        paper.resolve_write_intent(&Write::Answer(question("2"), AnswerEdit::Append("oxygen".to_string())));
+        paper.resolve_write_intent(&Write::Unmark(question("1")));
+        read(&mut paper, Read::Question(Reference::Label("3".to_string()))).unwrap();
+
+        assert_eq!(meta(&mut paper, MetaIntent::Progress), "Section A: 3 questions, 1 answered, 0 marked, 1 skipped, 2 remaining");
+
+        read(&mut paper, Read::Question(Reference::Label("4".to_string()))).unwrap();
+
+        assert_eq!(meta(&mut paper, MetaIntent::Progress), "Section B: 1 question, 0 answered, 0 marked, 1 remaining");
+    }
+
+    #[test]
+    fn session_restores_on_the_same_paper(){
+        let mut paper = paper();
+
+        paper.resolve_write_intent(&Write::Mark(question("2")));
+        paper.resolve_write_intent(&Write::Skip(question("3")));
+        read(&mut paper, Read::Question(Reference::Label("4".to_string()))).unwrap();
+
+        let session = serde_json::to_string(&paper.session()).unwrap();
+
+        let mut restored = self::paper();
+        restored.restore_session(serde_json::from_str(&session).unwrap()).unwrap();
+
+        let second = index_of(&restored, "2");
+
+        assert_eq!(restored.num_marked(), 1);
+        assert_eq!(restored.num_skipped(), 1);
+        assert_eq!(restored.status(index_of(&restored, "4")).map(NodeStatus::status), Some(Status::Visited));
+
+        match restored.nodes[second].data {
+            NodeData::Question(ref question) => assert!(question.marked),
+            _ => panic!("question 2 is not a question")
+        }
+    }
+
+    #[test]
+    fn session_from_another_paper_is_refused(){
+        let mut paper = paper();
+        let mut session = paper.session();
+
+        session.statuses.pop();
+
+        assert!(paper.restore_session(session).is_err());
+    }
+
+    fn indices<'a>(nodes: impl Iterator<Item = NodeIndex<'a>>) -> Vec<usize> {
+        nodes.map(|node| node.index()).collect()
+    }
+
+    #[test]
+    fn tree_navigation(){
+        let paper = paper();
+        let (first, second, third) = (index_of(&paper, "1"), index_of(&paper, "2"), index_of(&paper, "3"));
+        let node = paper.nth(second).unwrap();
+        let section = node.parent().unwrap();
+
+        assert_eq!(section.data().name(), "Section A");
+        assert_eq!(indices(section.children()), vec![first, second, third]);
+        assert_eq!(indices(section.descendants()), vec![first, second, third]);
+        assert_eq!(indices(node.ancestors()), vec![section.index(), 0]);
+        assert_eq!(indices(node.following_siblings()), vec![third]);
+        assert_eq!(indices(paper.nth(third).unwrap().preceding_siblings()), vec![second, first]);
+        assert_eq!(node.closest(&predicates::SectionPredicate).map(|node| node.index()), Some(section.index()));
+        assert_eq!(node.closest(&predicates::QuestionPredicate).map(|node| node.index()), Some(second));
+
+        // every node but the document is below it
+        let mut all = indices(paper.nth(0).unwrap().descendants());
+        all.sort_unstable();
+
+        assert_eq!(all, (1..paper.nodes.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn questions_in_the_current_section(){
+        let mut paper = paper();
+
+        assert_eq!(
+            read(&mut paper, Read::QuestionInSection(Reference::Start(1))),
+            Err(Cow::from("You are not in a section yet"))
+        );
+
+        read(&mut paper, Read::Question(Reference::Label("2".to_string()))).unwrap();
+
+        assert_eq!(read(&mut paper, Read::QuestionInSection(Reference::End(1))), Ok("question 3".to_string()));
+        assert_eq!(
+            read(&mut paper, Read::QuestionInSection(Reference::Current(1))),
+            Err(Cow::from("There are no more questions in this section"))
+        );
+        assert_eq!(read(&mut paper, Read::QuestionInSection(Reference::Start(1))), Ok("question 1".to_string()));
+    }
+
+    #[test]
+    fn predicates_combine(){
+        let mut paper = paper();
+        let first = index_of(&paper, "1");
+
+        paper.resolve_write_intent(&Write::Mark(question("1")));
+
+        let node = paper.nth(first).unwrap();
+
+        assert!(predicates::QuestionPredicate.and(predicates::MarkedPredicate).matches(&node));
+        assert!(!predicates::QuestionPredicate.and(predicates::SkippedPredicate).matches(&node));
+        assert!(predicates::SectionPredicate.or(predicates::QuestionPredicate).matches(&node));
+        assert!(!predicates::SectionPredicate.or(predicates::PagePredicate).matches(&node));
+        assert!(!predicates::MarkedPredicate.not().matches(&node));
+        assert!(predicates::SkippedPredicate.not().and(predicates::OnPagePredicate(1)).matches(&node));
+    }
+
+    fn question_where(reference: Reference, filters: Vec<Filter>) -> Read {
+        Read::QuestionWhere(reference, filters)
+    }
+
+    #[test]
+    fn filtered_question_reads(){
+        let mut paper = paper();
+
+        paper.resolve_write_intent(&Write::Skip(question("2")));
+        paper.resolve_write_intent(&Write::Skip(question("4")));
+
+        assert_eq!(read(&mut paper, question_where(Reference::Start(1), vec![Filter::Skipped])), Ok("question 2".to_string()));
+        assert_eq!(read(&mut paper, question_where(Reference::End(1), vec![Filter::Skipped])), Ok("question 4".to_string()));
+        assert_eq!(
+            read(&mut paper, question_where(Reference::Start(1), vec![Filter::Skipped, Filter::OnPage(2)])),
+            Ok("question 4".to_string())
+        );
+        assert_eq!(
+            read(&mut paper, question_where(Reference::Start(1), vec![Filter::Not(Box::new(Filter::Visited))])),
+            Ok("question 1".to_string())
+        );
+        assert_eq!(
+            read(&mut paper, question_where(Reference::Start(1), vec![Filter::Contains("GAS".to_string())])),
+            Ok("question 2".to_string())
+        );
+        assert_eq!(
+            read(&mut paper, question_where(Reference::Start(1), vec![Filter::Marked])),
+            Err(Cow::from("There is no question like that"))
+        );
+        assert_eq!(
+            read(&mut paper, question_where(Reference::Label("2".to_string()), vec![Filter::Skipped])),
+            Err(Cow::from("A question label cannot be combined with other conditions"))
+        );
+    }
+
+    const PASSAGE: &str = r#"<?xml version="1.0"?>
+        <root>
+        <SECTION_A>
+        <section_number>Section A</section_number>
+        <page_1>
+        <passage><p>Cells divide.</p><p>Tissues grow.</p></passage>
+        <item><question_number>1</question_number><question>What divides?</question></item>
+        <item><question_number>2</question_number><question>What grows?</question></item>
+        </page_1>
+        </SECTION_A>
+        <SECTION_B>
+        <section_number>Section B</section_number>
+        <page_2>
+        <item><question_number>3</question_number><question>Name an organ.</question></item>
+        <item><question_number>4</question_number><question>Name a system.</question></item>
+        </page_2>
+        </SECTION_B>
+        </root>"#;
+
+    #[test]
+    fn scoped_reads_resolve_inside_their_scope(){
+        let mut paper = build(PASSAGE);
+        let section_b = Read::Section(Reference::Label("B".to_string()));
+
+        assert_eq!(read(&mut paper, Read::Scoped(vec![section_b.clone(), Read::Question(Reference::Start(1))])), Ok("question 3".to_string()));
+        assert_eq!(
+            read(&mut paper, Read::Scoped(vec![section_b, Read::Question(Reference::Label("1".to_string()))])),
+            Err(Cow::from("There is no question 1 there"))
+        );
+        assert_eq!(
+            read(&mut paper, Read::Scoped(vec![Read::Page(Reference::Start(2)), Read::Question(Reference::End(1))])),
+            Ok("question 4".to_string())
+        );
+        assert_eq!(
+            read(&mut paper, Read::Scoped(vec![Read::Passage(Reference::Start(1)), Read::Paragraph(Reference::Start(2))])),
+            Ok("paragraph 2".to_string())
+        );
+        assert_eq!(
+            read(&mut paper, Read::Scoped(vec![Read::Passage(Reference::Start(1)), Read::Paragraph(Reference::Start(3))])),
+            Err(Cow::from("There is nothing at that position"))
+        );
+    }
+
+    fn range(from: &str, to: &str) -> Reference {
+        Reference::Range(Box::new(Reference::Label(from.to_string())), Box::new(Reference::Label(to.to_string())))
+    }
+
+    #[test]
+    fn ranges_read_every_question_in_them(){
+        let mut paper = paper();
+        let questions = vec!["question 2".to_string(), "question 3".to_string(), "question 4".to_string()];
+
+        assert_eq!(read_list(&mut paper, Read::Question(range("2", "4"))), Ok(questions.clone()));
+        assert_eq!(paper.prev_index(), index_of(&paper, "4"));
+
+        // the ends may be said in either order
+        assert_eq!(read_list(&mut paper, Read::Question(range("4", "2"))), Ok(questions));
+        assert_eq!(read_list(&mut paper, Read::Question(range("2", "9"))), Err(Cow::from("There is no question 9")));
+
+        assert_eq!(
+            read_list(&mut paper, Read::Scoped(vec![Read::Section(Reference::Label("A".to_string())), Read::Question(range("1", "4"))])),
+            Err(Cow::from("There is no question 4 there"))
+        );
+        assert_eq!(
+            read_list(&mut paper, Read::Scoped(vec![Read::Section(Reference::Label("A".to_string())), Read::Question(range("2", "3"))])),
+            Ok(vec!["question 2".to_string(), "question 3".to_string()])
+        );
+    }
+
+    #[test]
+    fn writes_over_a_range_are_confirmed_together(){
+        let mut paper = paper();
+
+        assert_eq!(
+            paper.resolve_write_intent(&Write::Mark(vec![Read::Question(range("1", "3"))])),
+            WriteResult::Success("Questions 1, 2 and 3 have been marked for review".to_string())
+        );
+        assert_eq!(paper.num_marked(), 3);
+    }
+
+    fn write(paper: &mut QuestionPaper, write: Write) -> Result<String, Cow<'static, str>> {
+        match paper.resolve_write_intent(&write) {
+            WriteResult::Success(message) => Ok(message),
+            WriteResult::Error(e) => Err(e),
+            result => panic!("expected the write to be done: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn only_questions_are_marked_and_skipped(){
+        let mut paper = paper();
+
+        assert_eq!(write(&mut paper, Write::Mark(vec![])), Err(Cow::from("Go to a question first")));
+
+        read(&mut paper, Read::Question(Reference::Label("2".to_string()))).unwrap();
+
+        // with nothing said the question being read is marked
+        assert_eq!(write(&mut paper, Write::Mark(vec![])), Ok("Question 2 has been marked for review".to_string()));
+        assert_eq!(write(&mut paper, Write::Skip(vec![])), Ok("Question 2 has been skipped".to_string()));
+        assert_eq!(
+            write(&mut paper, Write::Mark(vec![Read::Section(Reference::Label("B".to_string()))])),
+            Err(Cow::from("Could not find the questions you asked for"))
+        );
+        assert_eq!(
+            write(&mut paper, Write::Skip(vec![Read::Page(Reference::Start(2))])),
+            Err(Cow::from("Could not find the questions you asked for"))
+        );
+        assert_eq!(write(&mut paper, Write::Skip(question("9"))), Err(Cow::from("There is no question 9")));
+
+        assert_eq!(paper.num_marked(), 1);
+        assert_eq!(paper.num_skipped(), 1);
+    }
+
+    #[test]
+    fn bulk_writes_over_a_scope(){
+        let mut paper = paper();
+
+        assert_eq!(
+            write(&mut paper, Write::Bulk(BulkAction::Mark, vec![Read::Section(Reference::Label("A".to_string()))], vec![])),
+            Ok("3 questions have been marked for review".to_string())
+        );
+
+        read(&mut paper, Read::Question(Reference::Label("2".to_string()))).unwrap();
+
+        // "skip the rest of this section"
+        assert_eq!(
+            write(&mut paper, Write::Bulk(BulkAction::Skip, vec![], vec![Filter::AfterCurrent, Filter::InSection])),
+            Ok("1 question has been skipped".to_string())
+        );
+        assert_eq!(
+            write(&mut paper, Write::Bulk(BulkAction::ClearMark, vec![], vec![Filter::Marked])),
+            Ok("3 questions have been unmarked".to_string())
+        );
+        assert_eq!(
+            write(&mut paper, Write::Bulk(BulkAction::ClearMark, vec![], vec![Filter::Marked])),
+            Ok("No questions were unmarked".to_string())
+        );
+
+        assert_eq!(paper.num_marked(), 0);
+        assert!(paper.status(index_of(&paper, "3")).unwrap().is_skipped());
+    }
+
+    fn count(paper: &mut QuestionPaper, target: CountTarget, scope: Vec<Read>, filters: Vec<Filter>) -> Result<String, Cow<'static, str>> {
+        match paper.resolve_intent(Intent::Meta(MetaIntent::Count(target, scope, filters))) {
+            IntentResult::Count(result) => result.map(|count| count.describe()),
+            result => panic!("expected a count: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn counts(){
+        let mut paper = paper();
+        let section_b = || vec![Read::Section(Reference::Label("B".to_string()))];
+
+        assert_eq!(count(&mut paper, CountTarget::Questions, vec![], vec![]), Ok("There are 4 questions".to_string()));
+        assert_eq!(count(&mut paper, CountTarget::Sections, vec![], vec![]), Ok("There are 2 sections".to_string()));
+        assert_eq!(count(&mut paper, CountTarget::Questions, section_b(), vec![]), Ok("There is 1 question in Section B".to_string()));
+        assert_eq!(count(&mut paper, CountTarget::Tables, vec![], vec![]), Ok("There are 0 tables".to_string()));
+
+        paper.resolve_write_intent(&Write::Mark(question("1")));
+        paper.resolve_write_intent(&Write::Mark(question("3")));
+
+        assert_eq!(count(&mut paper, CountTarget::Questions, vec![], vec![Filter::Marked]), Ok("2 of 4 questions".to_string()));
+        assert_eq!(count(&mut paper, CountTarget::Questions, section_b(), vec![Filter::Marked]), Ok("0 of 1 question in Section B".to_string()));
+
+        // "in this section" counts the section being read
+        assert_eq!(count(&mut paper, CountTarget::Questions, vec![], vec![Filter::InSection]), Err(Cow::from("You are not in a section yet")));
+
+        read(&mut paper, Read::Question(Reference::Label("2".to_string()))).unwrap();
+
+        assert_eq!(
+            count(&mut paper, CountTarget::Questions, vec![], vec![Filter::InSection, Filter::Not(Box::new(Filter::Marked))]),
+            Ok("1 of 3 questions in Section A".to_string())
+        );
+    }
+
+    #[test]
+    fn count_results_are_structured(){
+        let mut paper = paper();
+
+        let result = paper.count(CountTarget::Pages, &[], &[]).unwrap();
+
+        assert_eq!(result, CountResult { target: CountTarget::Pages, count: 2, total: 2, scope: None });
+    }
+
+    fn answer_text(paper: &QuestionPaper, label: &str) -> Option<String> {
+        paper.answer(index_of(paper, label)).map(|answer| answer.text.clone())
+    }
+
+    #[test]
+    fn answers_are_dictated_to_the_question_being_answered(){
+        let mut paper = paper();
+
+        assert_eq!(
+            write(&mut paper, Write::Answer(vec![], AnswerEdit::Append("oxygen".to_string()))),
+            Err(Cow::from("Choose a question to answer first"))
+        );
+        assert_eq!(write(&mut paper, Write::Answer(question("3"), AnswerEdit::Start)), Ok("Answering question 3. Start dictating".to_string()));
+
+        // dictation keeps going to question 3 while other questions are read
+        read(&mut paper, Read::Question(Reference::Label("1".to_string()))).unwrap();
+        write(&mut paper, Write::Answer(vec![], AnswerEdit::Append("cells divide".to_string()))).unwrap();
+
+        assert_eq!(answer_text(&paper, "3"), Some("Cells divide".to_string()));
+        assert!(paper.status(index_of(&paper, "3")).unwrap().is_answered());
+
+        write(&mut paper, Write::Answer(vec![], AnswerEdit::Replace("they split in two".to_string()))).unwrap();
+        assert_eq!(answer_text(&paper, "3"), Some("They split in two".to_string()));
+
+        assert_eq!(write(&mut paper, Write::Answer(vec![], AnswerEdit::Stop)), Ok("Stopped answering question 3".to_string()));
+        assert_eq!(write(&mut paper, Write::Answer(question("3"), AnswerEdit::Clear)), Ok("Cleared your answer to question 3".to_string()));
+        assert!(!paper.status(index_of(&paper, "3")).unwrap().is_answered());
+        assert_eq!(
+            write(&mut paper, Write::Answer(question("2"), AnswerEdit::Clear)),
+            Err(Cow::from("There is nothing in your answer to question 2"))
+        );
+    }
+
+    #[test]
+    fn answers_are_kept_in_the_session(){
+        let mut paper = paper();
+
+        write(&mut paper, Write::Answer(question("3"), AnswerEdit::Append("it divides".to_string()))).unwrap();
+        write(&mut paper, Write::Answer(question("2"), AnswerEdit::Start)).unwrap();
+
+        let session = serde_json::to_string(&paper.session()).unwrap();
+
+        let mut restored = self::paper();
+        restored.restore_session(serde_json::from_str(&session).unwrap()).unwrap();
+
+        assert_eq!(answer_text(&restored, "3"), Some("It divides".to_string()));
+        assert_eq!(restored.answering, Some(index_of(&restored, "2")));
+    }
+
+    fn read_answer(paper: &mut QuestionPaper, read: AnswerRead) -> Result<String, Cow<'static, str>> {
+        match paper.resolve_intent(Intent::AnswerIntent(read)) {
+            IntentResult::Answer(result) => result,
+            result => panic!("expected an answer read: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn answers_are_read_back(){
+        let mut paper = paper();
+
+        assert_eq!(read_answer(&mut paper, AnswerRead::Whole(question("2"))), Err(Cow::from("You have not answered question 2")));
+
+        write(&mut paper, Write::Answer(question("2"), AnswerEdit::Start)).unwrap();
+        write(&mut paper, Write::Answer(vec![], AnswerEdit::Append("oxygen is a gas. So is neon".to_string()))).unwrap();
+
+        assert_eq!(read_answer(&mut paper, AnswerRead::Whole(vec![])), Ok("Your answer to question 2: Oxygen is a gas. So is neon".to_string()));
+        assert_eq!(read_answer(&mut paper, AnswerRead::Piece(TextUnit::Sentence, TextPosition::First)), Ok("Oxygen is a gas.".to_string()));
+        assert_eq!(read_answer(&mut paper, AnswerRead::Spell(Some("neon".to_string()))), Ok("neon is spelt n, e, o, n".to_string()));
+    }
+
+    #[test]
+    fn choices_are_checked_against_the_options(){
+        let mut paper = paper();
+        let first = index_of(&paper, "1");
+
+        assert_eq!(
+            paper.resolve_write_intent(&Write::Choose(question("1"), ChoiceEdit::Select(vec!["E".to_string()]))),
+            WriteResult::Error(Cow::from("Question 1 has no option E. Choose A or B"))
+        );
+
+        paper.resolve_write_intent(&Write::Choose(question("1"), ChoiceEdit::Select(vec!["option b".to_string()])));
+
+        assert_eq!(paper.status(first).unwrap().selected, vec!["B".to_string()]);
+        assert!(paper.status(first).unwrap().is_answered());
+        assert_eq!(
+            read_answer(&mut paper, AnswerRead::Choice(question("1"))),
+            Ok("For question 1 you chose B, the mitochondria".to_string())
+        );
+
+        // a single answer question keeps only the latest choice
+        paper.resolve_write_intent(&Write::Choose(question("1"), ChoiceEdit::Select(vec!["A".to_string()])));
+        assert_eq!(paper.status(first).unwrap().selected, vec!["A".to_string()]);
+
+        paper.resolve_write_intent(&Write::Choose(question("1"), ChoiceEdit::Deselect(vec!["A".to_string()])));
+        assert!(!paper.status(first).unwrap().is_answered());
+
+        assert_eq!(
+            paper.resolve_write_intent(&Write::Choose(question("2"), ChoiceEdit::Select(vec!["A".to_string()]))),
+            WriteResult::Error(Cow::from("Question 2 is not a multiple choice question"))
+        );
+    }
+
+    #[test]
+    fn maths_is_dictated_and_ambiguous_readings_confirmed(){
+        let mut paper = paper();
+        let third = index_of(&paper, "3");
+
+        write(&mut paper, Write::Answer(question("3"), AnswerEdit::Start)).unwrap();
+
+        assert_eq!(
+            write(&mut paper, Write::Answer(vec![], AnswerEdit::Math("x squared equals one two one".to_string()))),
+            Ok("Added x squared equals 121 to your answer to question 3".to_string())
+        );
+        assert_eq!(
+            paper.resolve_write_intent(&Write::Answer(vec![], AnswerEdit::Math("x over two plus one".to_string()))),
+            WriteResult::Confirm("Did you mean first, x over 2 plus 1; or second, the fraction with numerator x and denominator 2 plus 1? Say first or second".to_string())
+        );
+        assert_eq!(
+            write(&mut paper, Write::Answer(vec![], AnswerEdit::ConfirmMath(3))),
+            Err(Cow::from("Say first or second, there are 2 ways to read your maths"))
+        );
+
+        write(&mut paper, Write::Answer(vec![], AnswerEdit::ConfirmMath(2))).unwrap();
+
+        let latex: Vec<&str> = paper.answer(third).unwrap().math.iter().map(|math| math.latex.as_str()).collect();
+        assert_eq!(latex, vec!["{x}^{2} = 121", "\\frac{x}{2 + 1}"]);
+
+        assert_eq!(
+            write(&mut paper, Write::Answer(vec![], AnswerEdit::ConfirmMath(1))),
+            Err(Cow::from("There is no maths waiting to be confirmed"))
+        );
+        assert_eq!(
+            write(&mut paper, Write::Answer(vec![], AnswerEdit::Math("x plus".to_string()))),
+            Err(Cow::from("Your maths ended too early"))
+        );
+    }
+
+    #[test]
+    fn rough_work_is_kept_apart_and_not_marked(){
+        let mut paper = paper();
+        let third = index_of(&paper, "3");
+
+        assert_eq!(
+            write(&mut paper, Write::RoughWork(question("3"), AnswerEdit::Start)),
+            Ok("Writing rough work for question 3, it will not be marked. Start dictating".to_string())
+        );
+        write(&mut paper, Write::RoughWork(vec![], AnswerEdit::Append("two plus two".to_string()))).unwrap();
+
+        assert!(!paper.status(third).unwrap().is_answered());
+        assert_eq!(
+            read_answer(&mut paper, AnswerRead::Whole(vec![])),
+            Ok("Rough work for question 3, not marked: Two plus two".to_string())
+        );
+        assert_eq!(
+            read_answer(&mut paper, AnswerRead::RoughWork(question("2"))),
+            Err(Cow::from("You have no rough work for question 2"))
+        );
+        assert_eq!(write(&mut paper, Write::RoughWork(vec![], AnswerEdit::Stop)), Ok("Stopped rough work for question 3".to_string()));
+
+        // the session keeps rough work beside the answers, not in them
+        let session = paper.session();
+
+        assert!(session.answers.is_empty());
+        assert_eq!(session.rough_work.get(&third).map(|answer| answer.text.as_str()), Some("Two plus two"));
+    }
+
+    fn read_notes(paper: &mut QuestionPaper, read: NoteRead) -> Result<String, Cow<'static, str>> {
+        match paper.resolve_intent(Intent::NoteIntent(read)) {
+            IntentResult::Notes(result) => result,
+            result => panic!("expected a note read: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn notes_are_read_per_node_and_listed(){
+        let mut paper = paper();
+
+        assert_eq!(read_notes(&mut paper, NoteRead::All), Err(Cow::from("You have not taken any notes")));
+        assert_eq!(
+            write(&mut paper, Write::Note(question("1"), "check the units".to_string())),
+            Ok("Took a note on question 1".to_string())
+        );
+        write(&mut paper, Write::Note(vec![Read::Section(Reference::Label("B".to_string()))], "leave time for this".to_string())).unwrap();
+        assert_eq!(
+            write(&mut paper, Write::Note(question("2"), "".to_string())),
+            Err(Cow::from("There was nothing to note"))
+        );
+
+        assert_eq!(
+            read_notes(&mut paper, NoteRead::On(question("1"))),
+            Ok("1 note. On question 1: Check the units.".to_string())
+        );
+        assert_eq!(
+            read_notes(&mut paper, NoteRead::All),
+            Ok("2 notes. On question 1: Check the units. On Section B: Leave time for this.".to_string())
+        );
+        assert_eq!(
+            read_notes(&mut paper, NoteRead::On(question("2"))),
+            Err(Cow::from("There are no notes on question 2"))
+        );
+    }
+
+    #[test]
+    fn notes_are_edited_and_deleted(){
+        let mut paper = paper();
+
+        assert_eq!(
+            write(&mut paper, Write::EditNote(NoteRef::That, "units are metres".to_string())),
+            Err(Cow::from("Read or take a note first, then say which note"))
+        );
+
+        write(&mut paper, Write::Note(question("1"), "check the units".to_string())).unwrap();
+
+        // "that note" is the one just taken
+        assert_eq!(
+            write(&mut paper, Write::EditNote(NoteRef::That, "units are metres".to_string())),
+            Ok("Changed your note on question 1".to_string())
+        );
+        assert_eq!(read_notes(&mut paper, NoteRead::All), Ok("1 note. On question 1: Units are metres.".to_string()));
+
+        write(&mut paper, Write::Note(question("1"), "show working".to_string())).unwrap();
+
+        assert_eq!(
+            write(&mut paper, Write::DeleteNote(NoteRef::On(question("1")))),
+            Ok("Deleted 2 notes on question 1".to_string())
+        );
+        assert_eq!(read_notes(&mut paper, NoteRead::All), Err(Cow::from("You have not taken any notes")));
+        assert_eq!(
+            write(&mut paper, Write::DeleteNote(NoteRef::That)),
+            Err(Cow::from("Read or take a note first, then say which note"))
+        );
+    }
+
+    #[test]
+    fn notes_are_kept_in_the_session(){
+        let mut paper = paper();
+
+        write(&mut paper, Write::Note(question("4"), "check the units".to_string())).unwrap();
+
+        let session = serde_json::to_string(&paper.session()).unwrap();
+
+        let mut restored = self::paper();
+        restored.restore_session(serde_json::from_str(&session).unwrap()).unwrap();
+
+        assert_eq!(read_notes(&mut restored, NoteRead::All), Ok("1 note. On question 4: Check the units.".to_string()));
+    }
+
+    #[test]
+    fn marks_and_skips_are_taken_back(){
+        let mut paper = paper();
+        let second = index_of(&paper, "2");
+
+        assert_eq!(
+            write(&mut paper, Write::Unmark(question("2"))),
+            Err(Cow::from("Nothing was unmarked, question 2 is not marked for review"))
+        );
+
+        write(&mut paper, Write::Mark(question("2"))).unwrap();
+        write(&mut paper, Write::Skip(question("2"))).unwrap();
+
+        assert_eq!(write(&mut paper, Write::Unmark(question("2"))), Ok("Unmarked question 2".to_string()));
+        assert_eq!(write(&mut paper, Write::Unskip(question("2"))), Ok("Unskipped question 2".to_string()));
+        assert!(!paper.status(second).unwrap().is_marked());
+        assert!(!paper.status(second).unwrap().is_skipped());
+
+        match paper.nodes[second].data {
+            NodeData::Question(ref question) => assert!(!question.marked),
+            _ => panic!("question 2 is not a question")
+        }
+
+        assert_eq!(
+            write(&mut paper, Write::Unskip(question("3"))),
+            Err(Cow::from("Nothing was unskipped, question 3 is not skipped"))
+        );
+    }
+
+    #[test]
+    fn undo_covers_answers_and_notes(){
+        let mut paper = paper();
+        let third = index_of(&paper, "3");
+
+        assert_eq!(write(&mut paper, Write::Undo), Err(Cow::from("There is nothing to undo")));
+
+        write(&mut paper, Write::Answer(question("3"), AnswerEdit::Start)).unwrap();
+        write(&mut paper, Write::Answer(vec![], AnswerEdit::Append("cells divide".to_string()))).unwrap();
+        write(&mut paper, Write::Note(question("3"), "check the units".to_string())).unwrap();
+
+        assert_eq!(write(&mut paper, Write::Undo), Ok("Undone: Took a note on question 3".to_string()));
+        assert!(paper.notes().is_empty());
+
+        write(&mut paper, Write::Undo).unwrap();
+        assert_eq!(answer_text(&paper, "3"), None);
+        assert!(!paper.status(third).unwrap().is_answered());
+
+        // a new change can not be redone past
+        write(&mut paper, Write::Mark(question("1"))).unwrap();
+        assert_eq!(write(&mut paper, Write::Redo), Err(Cow::from("There is nothing to redo")));
+    }
+
+    #[test]
+    fn going_back_is_not_the_previous_question(){
+        let mut paper = paper();
+
+        match paper.resolve_intent(Intent::VisitIntent(Visit::Back)) {
+            IntentResult::Read(Err(e)) => assert_eq!(e, "There is nowhere to go back to"),
+            result => panic!("went back without a visit: {:?}", result)
+        }
+
+        read(&mut paper, Read::Question(Reference::Label("1".to_string()))).unwrap();
+        read(&mut paper, Read::Question(Reference::Label("4".to_string()))).unwrap();
+
+        match paper.resolve_intent(Intent::VisitIntent(Visit::Back)) {
+            IntentResult::Read(Ok(data)) => assert_eq!(data.name(), "question 1"),
+            result => panic!("could not go back: {:?}", result)
+        }
+
+        match paper.resolve_intent(Intent::VisitIntent(Visit::Forward)) {
+            IntentResult::Read(Ok(data)) => assert_eq!(data.name(), "question 4"),
+            result => panic!("could not go forward: {:?}", result)
+        }
+
+        match paper.resolve_intent(Intent::VisitIntent(Visit::Forward)) {
+            IntentResult::Read(Err(e)) => assert_eq!(e, "There is nowhere to go forward to"),
+            result => panic!("went forward past the last visit: {:?}", result)
+        }
+
+        // an offset moves in document order, going back moves through the visits
+        assert_eq!(read(&mut paper, Read::Question(Reference::Current(-1))), Ok("question 3".to_string()));
+
+        match paper.resolve_intent(Intent::VisitIntent(Visit::Before)) {
+            IntentResult::Meta(message) => assert_eq!(message, "Before this you were on question 4"),
+            result => panic!("could not say where it was before: {:?}", result)
+        }
+    }
+}
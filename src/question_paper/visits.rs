@@ -0,0 +1,109 @@
+// how many nodes visited are kept to go back to
+const VISITS_LIMIT: usize = 100;
+
+/// The nodes the candidate has been on, in the order they went to them.
+/// Going back and forward works like a browser, unlike offsets which move in document order
+#[derive(Debug, Clone, Default)]
+pub struct Visits {
+    // nodes left for another, most recent last
+    back: Vec<usize>,
+    // nodes gone back from, most recent last
+    forward: Vec<usize>
+}
+
+impl Visits {
+    /// Keep the node left when the candidate goes to another, going somewhere new ends the way forward
+    pub fn leave(&mut self, index: usize) {
+        if self.back.len() == VISITS_LIMIT {
+            self.back.remove(0);
+        }
+
+        self.back.push(index);
+        self.forward.clear();
+    }
+
+    /// The node visited before the current one, the current one is kept to go forward to
+    pub fn back(&mut self, current: usize) -> Option<usize> {
+        let index = self.back.pop()?;
+
+        self.forward.push(current);
+
+        Some(index)
+    }
+
+    /// The node gone back from, the current one is kept to go back to
+    pub fn forward(&mut self, current: usize) -> Option<usize> {
+        let index = self.forward.pop()?;
+
+        self.back.push(current);
+
+        Some(index)
+    }
+
+    /// The node visited before the current one, without going to it
+    pub fn previous(&self) -> Option<usize> {
+        self.back.last().cloned()
+    }
+
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_and_forward(){
+        let mut visits = Visits::default();
+
+        // 0 -> 2 -> 15
+        visits.leave(0);
+        visits.leave(2);
+
+        assert_eq!(visits.previous(), Some(2));
+        assert_eq!(visits.back(15), Some(2));
+        assert_eq!(visits.back(2), Some(0));
+        assert_eq!(visits.back(0), None);
+        assert_eq!(visits.forward(0), Some(2));
+        assert_eq!(visits.forward(2), Some(15));
+        assert_eq!(visits.forward(15), None);
+    }
+
+    #[test]
+    fn going_somewhere_new_ends_forward(){
+        let mut visits = Visits::default();
+
+        visits.leave(1);
+        visits.back(2);
+        visits.leave(1);
+
+        assert_eq!(visits.forward(3), None);
+        assert_eq!(visits.previous(), Some(1));
+    }
+
+    #[test]
+    fn oldest_visits_are_dropped(){
+        let mut visits = Visits::default();
+
+        for index in 0..VISITS_LIMIT + 10 {
+            visits.leave(index);
+        }
+
+        let mut current = VISITS_LIMIT + 10;
+        let mut count = 0;
+
+        while let Some(index) = visits.back(current) {
+            current = index;
+            count += 1;
+        }
+
+        assert_eq!(count, VISITS_LIMIT);
+        assert_eq!(current, 10);
+
+        visits.clear();
+        assert_eq!(visits.previous(), None);
+    }
+}